@@ -0,0 +1,78 @@
+//! Compares the slider attack backends by running the same perft positions with each
+//!
+//! Run with `cargo bench --features pext`, without the feature only the magic backend is
+//! measured.
+
+use rust_chess::pieces::board::Board;
+use rust_chess::pieces::fen::START_FEN;
+use rust_chess::pieces::magic::{self, Backend};
+use std::time::{Duration, Instant};
+
+/// Name, FEN and depth of each position
+const POSITIONS: [(&str, &str, usize); 6] = [
+    ("startpos", START_FEN, 5),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        5,
+    ),
+    ("position 3", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 6),
+    (
+        "position 4",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        5,
+    ),
+    (
+        "position 5",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        5,
+    ),
+    (
+        "position 6",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        4,
+    ),
+];
+
+/// Runs every position with the current backend, returns the nodes and the time taken
+fn run() -> (u64, Duration) {
+    let mut total = 0;
+    let start = Instant::now();
+    for (name, fen, depth) in POSITIONS {
+        let mut board = Board::from_fen(fen).unwrap();
+        let position_start = Instant::now();
+        let nodes = board.perft(depth);
+        println!(
+            "  {:<12} depth {} {:>10} nodes {:>8} ms",
+            name,
+            depth,
+            nodes,
+            position_start.elapsed().as_millis()
+        );
+        total += nodes;
+    }
+    (total, start.elapsed())
+}
+
+fn main() {
+    magic::init();
+
+    let mut backends = vec![Backend::Magic];
+    if magic::pext_available() {
+        backends.push(Backend::Pext);
+    } else {
+        println!("pext backend unavailable, build with --features pext on a BMI2 CPU");
+    }
+
+    for backend in backends {
+        assert!(magic::set_backend(backend));
+        println!("{:?}", backend);
+        let (nodes, elapsed) = run();
+        println!(
+            "  total {} nodes in {} ms, {} nps",
+            nodes,
+            elapsed.as_millis(),
+            (nodes as f64 / elapsed.as_secs_f64()) as u64
+        );
+    }
+}
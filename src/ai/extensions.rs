@@ -0,0 +1,36 @@
+/// Shallowest depth the singular extension is tried at
+pub const SINGULAR_DEPTH: usize = 8;
+/// How much shallower than the node the TT entry may be to be trusted for a singular search
+pub const SINGULAR_TT_DEPTH: usize = 3;
+/// Margin per ply of depth the other moves must stay under the TT score by
+pub const SINGULAR_MARGIN: i32 = 2;
+
+/// Returns the bound every move but the TT move must fail low against for the TT move to
+/// count as singular
+pub fn singular_beta(tt_score: i32, depth: usize) -> i32 {
+    tt_score - SINGULAR_MARGIN * depth as i32
+}
+
+/// Returns the depth of the search verifying that no other move comes close to the TT move
+pub fn singular_depth(depth: usize) -> usize {
+    (depth - 1) / 2
+}
+
+/// Returns true if a node `ply` plies from the root may still be extended: no line grows
+/// past twice the depth of the iteration, however many forcing moves it has
+pub fn may_extend(ply: usize, root_depth: usize) -> bool {
+    ply < 2 * root_depth
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(singular_beta(50, 10), 30);
+        assert_eq!(singular_depth(SINGULAR_DEPTH), 3);
+        assert!(may_extend(9, 5));
+        assert!(!may_extend(10, 5));
+    }
+}
@@ -0,0 +1,78 @@
+use super::tt::Bound;
+use crate::pieces::board::Move;
+use std::time::Duration;
+
+/// How long a search runs before it reports the root move it is searching
+pub const CURRMOVE_DELAY: Duration = Duration::from_millis(1000);
+
+/// State of the search after an iteration, or after a root search that failed outside its
+/// aspiration window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IterationInfo {
+    pub depth: usize,
+    pub seldepth: usize,
+    /// Rank of the line among the best lines, starting at 1
+    pub multipv: usize,
+    /// Score from the side to move's point of view
+    pub score: i32,
+    /// [`Bound::Exact`], or whether the score is only a lower or upper bound
+    pub bound: Bound,
+    /// Nodes searched since the search started
+    pub nodes: u64,
+    /// Time since the search started
+    pub time: Duration,
+    /// Transposition table usage in permille
+    pub hashfull: usize,
+    /// Tablebase probes that found the position
+    pub tbhits: u64,
+    pub pv: Vec<Move>,
+}
+
+impl IterationInfo {
+    /// Returns the nodes searched per second
+    pub fn nps(&self) -> u64 {
+        (self.nodes as u128 * 1000 / self.time.as_millis().max(1)) as u64
+    }
+}
+
+/// Something a running search has to tell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Info {
+    Iteration(IterationInfo),
+    /// The root move being searched, `number` counting from 1 in search order
+    CurrMove {
+        depth: usize,
+        mv: Move,
+        number: usize,
+    },
+    /// Diagnostics meant for a human
+    String(String),
+}
+
+/// Receives the reports of a running search
+pub type InfoSink = Box<dyn FnMut(&Info) + Send>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_nps() {
+        let mut info = IterationInfo {
+            depth: 1,
+            seldepth: 1,
+            multipv: 1,
+            score: 0,
+            bound: Bound::Exact,
+            nodes: 5000,
+            time: Duration::from_millis(250),
+            hashfull: 0,
+            tbhits: 0,
+            pv: Vec::new(),
+        };
+        assert_eq!(info.nps(), 20_000);
+        // No division by zero at the very start
+        info.time = Duration::ZERO;
+        assert_eq!(info.nps(), 5_000_000);
+    }
+}
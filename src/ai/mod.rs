@@ -1,53 +1,1131 @@
-use crate::pieces::{board::Board, traits::Piece};
-use std::cmp::{max, min};
+pub mod extensions;
+pub mod info;
+pub mod ordering;
+pub mod pruning;
+pub mod quiescence;
+pub mod time;
+pub mod tt;
+
+use crate::pieces::board::{Board, Move};
+use extensions::{may_extend, singular_beta, singular_depth, SINGULAR_DEPTH, SINGULAR_TT_DEPTH};
+use info::{Info, InfoSink, IterationInfo, CURRMOVE_DELAY};
+use ordering::{MovePicker, SearchData};
+use pruning::{
+    null_move_depth, reduction, FUTILITY_DEPTH, FUTILITY_MARGIN, LMR_DEPTH, LMR_MOVES,
+    NULL_MOVE_DEPTH, REVERSE_FUTILITY_DEPTH, REVERSE_FUTILITY_MARGIN,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use time::{TimeManager, DEFAULT_MOVE_OVERHEAD};
+use tt::{Bound, TranspositionTable};
+
+/// Larger than any score a search returns
+pub const INFINITY: i32 = 32_000;
+/// Score of being checkmated right now, mates further away score closer to zero
+pub const MATE: i32 = 30_000;
+/// Deepest ply a search can reach
+pub const MAX_PLY: usize = 128;
+/// Scores at least this large in magnitude are mate scores
+pub const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+/// Score of a drawn position
+pub const DRAW: i32 = 0;
+/// Default half width of the root aspiration window in centipawns
+pub const DEFAULT_ASPIRATION_WINDOW: i32 = 25;
+/// Widest aspiration window that can be set, 0 searches every iteration with a full window
+pub const MAX_ASPIRATION_WINDOW: i32 = 1000;
+/// Most best lines a search can be asked for
+pub const MAX_MULTI_PV: usize = 256;
+/// First iteration that searches with an aspiration window
+const ASPIRATION_DEPTH: usize = 4;
+
+/// Search parameters that can be tuned from outside
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    /// Half width of the window around the previous score each iteration starts with
+    pub aspiration_window: i32,
+    /// Let the opponent move twice and cut if the position still holds above beta
+    pub null_move: bool,
+    /// Search late quiet moves shallower, again at full depth if they turn out good
+    pub late_move_reductions: bool,
+    /// Cut when the static eval beats beta by a margin growing with depth
+    pub reverse_futility: bool,
+    /// Skip quiet moves near the leaves when the static eval is far below alpha
+    pub futility: bool,
+    /// Search a capture on the square the opponent just captured on one ply deeper
+    pub recapture_extension: bool,
+    /// Time kept back from every time limit for the GUI and the connection
+    pub move_overhead: Duration,
+    /// Number of best lines searched and reported
+    pub multi_pv: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            aspiration_window: DEFAULT_ASPIRATION_WINDOW,
+            null_move: true,
+            late_move_reductions: true,
+            reverse_futility: true,
+            futility: true,
+            recapture_extension: false,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            multi_pv: 1,
+        }
+    }
+}
+
+/// When a search has to stop, every limit left out is unlimited
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Deepest iteration to search
+    pub depth: Option<usize>,
+    /// Nodes to search before stopping
+    pub nodes: Option<u64>,
+    /// Stop once a mate in at most this many moves is found
+    pub mate: Option<usize>,
+    /// Time left on white's clock
+    pub wtime: Option<Duration>,
+    /// Time left on black's clock
+    pub btime: Option<Duration>,
+    /// White's increment per move
+    pub winc: Option<Duration>,
+    /// Black's increment per move
+    pub binc: Option<Duration>,
+    /// Moves until the next time control
+    pub movestogo: Option<u32>,
+    /// Exact time to search
+    pub movetime: Option<Duration>,
+    /// Search until told to stop, ignoring the clock
+    pub infinite: bool,
+    /// Search on the opponent's time, ignoring the clock until the ponder move is played
+    pub ponder: bool,
+    /// Only consider these root moves, every move if empty
+    pub search_moves: Vec<Move>,
+}
+
+/// Flags another thread uses to steer a running search
+#[derive(Debug, Default)]
+pub struct Signals {
+    /// Set to end the search as soon as possible
+    pub stop: AtomicBool,
+    /// Set while the search runs on the opponent's time, cleared on `ponderhit`
+    pub ponder: AtomicBool,
+}
+
+/// How often each search extension fired
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Moves giving check
+    pub check_extensions: u64,
+    /// TT moves much better than every alternative
+    pub singular_extensions: u64,
+    /// Captures taking back on the square of the previous capture
+    pub recapture_extensions: u64,
+}
+
+/// Outcome of [`Ai::search`], taken from the last completed iteration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// Best move, [`Move::NULL`] if the side to move has none
+    pub best_move: Move,
+    /// Score from the side to move's point of view
+    pub score: i32,
+    /// Depth of the last completed iteration
+    pub depth: usize,
+    /// Deepest ply reached
+    pub seldepth: usize,
+    /// Nodes searched over all iterations
+    pub nodes: u64,
+    /// Principal variation, starting with the best move
+    pub pv: Vec<Move>,
+    /// Move expected in reply to the best move, [`Move::NULL`] if none is known
+    pub ponder_move: Move,
+    /// The best [`Params::multi_pv`] lines, best first, each starting with a different move
+    pub lines: Vec<Line>,
+    /// Extensions over all iterations
+    pub stats: SearchStats,
+}
+
+/// One of the best lines of a MultiPV search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// Score from the side to move's point of view
+    pub score: i32,
+    /// Principal variation, starting with the root move of the line
+    pub pv: Vec<Move>,
+}
+
+/// Triangular table of principal variations: row `ply` holds the best line found from
+/// `ply` on, in columns `ply..len[ply]`
+struct PvTable {
+    moves: Vec<[Move; MAX_PLY]>,
+    len: [usize; MAX_PLY],
+}
+
+impl Default for PvTable {
+    fn default() -> Self {
+        PvTable {
+            moves: vec![[Move::NULL; MAX_PLY]; MAX_PLY],
+            len: [0; MAX_PLY],
+        }
+    }
+}
+
+impl PvTable {
+    /// Empties the line of a ply before its node is searched
+    fn clear(&mut self, ply: usize) {
+        self.len[ply] = ply;
+    }
+
+    /// Makes `mv` followed by the line of the next ply the line of `ply`
+    fn update(&mut self, ply: usize, mv: Move) {
+        let (rows, next) = self.moves.split_at_mut(ply + 1);
+        let len = self.len[ply + 1].max(ply + 1);
+        rows[ply][ply] = mv;
+        rows[ply][ply + 1..len].copy_from_slice(&next[0][ply + 1..len]);
+        self.len[ply] = len;
+    }
+
+    /// Returns a copy of the line of `ply`, for a search that reuses the row to put it back
+    fn save(&self, ply: usize) -> Vec<Move> {
+        self.moves[ply][ply..self.len[ply]].to_vec()
+    }
+
+    /// Makes `line` the line of `ply` again
+    fn restore(&mut self, ply: usize, line: &[Move]) {
+        self.moves[ply][ply..ply + line.len()].copy_from_slice(line);
+        self.len[ply] = ply + line.len();
+    }
+
+    /// Returns the line from the root
+    fn line(&self) -> &[Move] {
+        &self.moves[0][..self.len[0]]
+    }
+}
 
 /// AI engine
 /// board_stack: Vec of boards
+/// tt: results of earlier searches, shared by every node
+/// data: move ordering tables of this search thread
+/// params: tunable search parameters
+/// signals: stop and ponder flags, shared with whoever controls the search
+/// info: receives the progress of a search, nothing is reported without one
+#[derive(Default)]
 pub struct Ai {
     pub board_stack: Vec<Board>,
+    pub tt: TranspositionTable,
+    pub data: SearchData,
+    pub params: Params,
+    pub signals: Arc<Signals>,
+    pub info: Option<InfoSink>,
+    limits: Limits,
+    time: TimeManager,
+    root_depth: usize,
+    nodes: u64,
+    stats: SearchStats,
+    seldepth: usize,
+    stopped: bool,
+    pv: PvTable,
+    /// Index of the MultiPV line being searched, counting from 0
+    pv_index: usize,
+    /// Root moves of the better lines of this iteration, left out of the search for the next
+    root_excluded: Vec<Move>,
 }
 
-impl Default for Ai {
-    /// Default AI
-    fn default() -> Self {
-        let mut board_stack: Vec<Board> = Vec::new();
+impl Ai {
+    /// Searches the position with iterative deepening until a limit is reached
+    ///
+    /// The clock is ignored while [`Signals::ponder`] is set, which the caller has to do
+    /// before starting a pondering search. Once it is cleared the search goes on with the
+    /// time limits counting from then. An infinite or pondering search that runs out of depth
+    /// waits for [`Signals::stop`] or the end of pondering before returning.
+    ///
+    /// With [`Params::multi_pv`] above 1 every iteration searches the root once per line,
+    /// each time without the root moves of the lines found before.
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::ai::{Ai, Limits};
+    /// use rust_chess::pieces::board::Board;
+    ///
+    /// let mut board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let limits = Limits { depth: Some(3), ..Limits::default() };
+    /// let result = Ai::default().search(&mut board, limits);
+    /// assert_eq!(result.best_move.to_string(), "a1a8");
+    /// ```
+    pub fn search(&mut self, board: &mut Board, limits: Limits) -> SearchResult {
+        self.tt.new_search();
+        self.data.new_search();
+        self.time = TimeManager::new(&limits, board.state().stm(), self.params.move_overhead);
+        self.limits = limits;
+        self.nodes = 0;
+        self.stats = SearchStats::default();
+        self.seldepth = 0;
+        self.stopped = false;
+
+        // Play something even if the first iteration does not finish
+        let mut result = SearchResult {
+            best_move: self
+                .root_moves(board)
+                .first()
+                .copied()
+                .unwrap_or(Move::NULL),
+            score: 0,
+            depth: 0,
+            seldepth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+            ponder_move: Move::NULL,
+            lines: Vec::new(),
+            stats: SearchStats::default(),
+        };
+
+        // At least one line, to find mate and stalemate without root moves
+        let multi_pv = self
+            .params
+            .multi_pv
+            .min(self.root_moves(board).len())
+            .max(1);
+        let max_depth = self.limits.depth.unwrap_or(MAX_PLY - 1).min(MAX_PLY - 1);
+        let mut stability = 0;
+        for depth in 1..=max_depth {
+            self.root_depth = depth;
+            self.root_excluded.clear();
+            let mut lines = Vec::with_capacity(multi_pv);
+            for pv_index in 0..multi_pv {
+                self.pv_index = pv_index;
+                let previous = result.lines.get(pv_index).map_or(0, |line| line.score);
+                let score = self.aspiration(board, depth, previous);
+                if self.stopped {
+                    break;
+                }
+                let pv = self.complete_pv(board, depth);
+                self.root_excluded.extend(pv.first());
+                lines.push(Line { score, pv });
+            }
+            self.root_excluded.clear();
+            if self.stopped {
+                break;
+            }
+
+            // A later line may score above an earlier one that failed soft against a lower
+            // bound
+            lines.sort_by_key(|line| -line.score);
+            let Line { score, pv } = lines[0].clone();
+            let score_drop = result.score - score;
+            if pv.first() == Some(&result.best_move) {
+                stability += 1;
+            } else {
+                stability = 0;
+            }
+            for (rank, line) in lines.iter().enumerate() {
+                let info = self.iteration_info(rank + 1, line.score, Bound::Exact, line.pv.clone());
+                self.report(Info::Iteration(info));
+            }
+            result = SearchResult {
+                best_move: pv.first().copied().unwrap_or(Move::NULL),
+                score,
+                depth,
+                seldepth: self.seldepth,
+                nodes: self.nodes,
+                pv,
+                ponder_move: Move::NULL,
+                lines,
+                stats: self.stats,
+            };
+
+            // No deeper iteration finds a shorter mate, but the other lines may still change
+            let mate_plies = (MATE - score.abs()) as usize;
+            if score.abs() >= MATE_BOUND
+                && mate_plies <= depth
+                && multi_pv == 1
+                && !self.limits.infinite
+            {
+                break;
+            }
+            if score >= MATE_BOUND
+                && self
+                    .limits
+                    .mate
+                    .is_some_and(|moves| mate_plies.div_ceil(2) <= moves)
+            {
+                break;
+            }
+            self.check_ponderhit();
+            if depth > 1 && !self.pondering() && self.time.stop_iterating(stability, score_drop) {
+                break;
+            }
+        }
 
-        Ai { board_stack }
+        // The GUI expects no best move before it ends an infinite or pondering search
+        while (self.limits.infinite || self.pondering()) && !self.stop_signaled() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        result.nodes = self.nodes;
+        result.stats = self.stats;
+        result.ponder_move = self.ponder_move(board, result.best_move, &result.pv);
+        self.report(Info::String(format!(
+            "extensions check {} singular {} recapture {}",
+            self.stats.check_extensions,
+            self.stats.singular_extensions,
+            self.stats.recapture_extensions
+        )));
+        result
+    }
+
+    /// Hands `info` to the sink, if there is one
+    fn report(&mut self, info: Info) {
+        if let Some(sink) = self.info.as_mut() {
+            sink(&info);
+        }
+    }
+
+    /// Returns the state of the search with a root score and the line leading to it
+    fn iteration_info(
+        &self,
+        multipv: usize,
+        score: i32,
+        bound: Bound,
+        pv: Vec<Move>,
+    ) -> IterationInfo {
+        IterationInfo {
+            depth: self.root_depth,
+            seldepth: self.seldepth,
+            multipv,
+            score,
+            bound,
+            nodes: self.nodes,
+            time: self.time.elapsed(),
+            hashfull: self.tt.hashfull(),
+            tbhits: 0,
+            pv,
+        }
+    }
+
+    /// Returns true once the search has run long enough for progress within an iteration to
+    /// be worth reporting
+    fn verbose(&self) -> bool {
+        self.info.is_some() && self.time.elapsed() >= CURRMOVE_DELAY
+    }
+
+    /// Searches the root with a window around the score of the previous iteration, widening
+    /// the side that failed until the score falls inside
+    fn aspiration(&mut self, board: &mut Board, depth: usize, previous: i32) -> i32 {
+        let mut delta = self.params.aspiration_window;
+        if depth < ASPIRATION_DEPTH || delta <= 0 || previous.abs() >= MATE_BOUND {
+            return self.alpha_beta(board, depth, 0, -INFINITY, INFINITY);
+        }
+
+        let mut alpha = (previous - delta).max(-INFINITY);
+        let mut beta = (previous + delta).min(INFINITY);
+        loop {
+            let score = self.alpha_beta(board, depth, 0, alpha, beta);
+            if self.stopped {
+                return score;
+            }
+
+            let bound = if score <= alpha {
+                Bound::Upper
+            } else if score >= beta {
+                Bound::Lower
+            } else {
+                return score;
+            };
+            if self.verbose() {
+                let pv = self.pv.line().to_vec();
+                let info = self.iteration_info(self.pv_index + 1, score, bound, pv);
+                self.report(Info::Iteration(info));
+                self.report(Info::String(format!(
+                    "depth {depth} failed {} the window [{alpha}, {beta}]",
+                    if bound == Bound::Upper { "low" } else { "high" }
+                )));
+            }
+
+            if bound == Bound::Upper {
+                // Fail low: pull beta in as well, the true score is below the old window
+                beta = (alpha + beta) / 2;
+                alpha = (score - delta).max(-INFINITY);
+            } else {
+                beta = (score + delta).min(INFINITY);
+            }
+            delta += delta / 2;
+        }
+    }
+
+    /// Returns the line of the PV table, continued with best moves from the TT where a
+    /// transposition cut it short
+    fn complete_pv(&self, board: &mut Board, depth: usize) -> Vec<Move> {
+        let mut pv = self.pv.line().to_vec();
+        let mut undos = Vec::new();
+        for mv in &pv {
+            undos.push(board.make_move(*mv));
+        }
+        while pv.len() < depth {
+            let Some(entry) = self.tt.probe(board.hash(), pv.len()) else {
+                break;
+            };
+            if entry.mv == Move::NULL || !board.legal_moves().contains(&entry.mv) {
+                break;
+            }
+            undos.push(board.make_move(entry.mv));
+            pv.push(entry.mv);
+        }
+        for (mv, undo) in pv.iter().zip(undos).rev() {
+            board.unmake_move(*mv, undo);
+        }
+        pv
+    }
+
+    /// Returns the legal root moves, only those of `searchmoves` if it was given, without
+    /// those of the better MultiPV lines
+    fn root_moves(&self, board: &Board) -> Vec<Move> {
+        let mut moves = board.legal_moves();
+        if !self.limits.search_moves.is_empty() {
+            moves.retain(|mv| self.limits.search_moves.contains(mv));
+        }
+        moves.retain(|mv| !self.root_excluded.contains(mv));
+        moves
+    }
+
+    /// Returns true while the search runs on the opponent's time
+    fn pondering(&self) -> bool {
+        self.signals.ponder.load(Ordering::Relaxed)
+    }
+
+    /// Returns true once the search was told to stop
+    fn stop_signaled(&self) -> bool {
+        self.signals.stop.load(Ordering::Relaxed)
+    }
+
+    /// Starts the clock over once a pondering search learns that the opponent played the
+    /// expected move: its time limits count from then
+    fn check_ponderhit(&mut self) {
+        if self.limits.ponder && !self.pondering() {
+            self.limits.ponder = false;
+            self.time.restart();
+        }
+    }
+
+    /// Returns the move expected in reply to `best_move`: the second move of `pv`, or the
+    /// best move the TT knows after `best_move` when the line is shorter
+    fn ponder_move(&self, board: &mut Board, best_move: Move, pv: &[Move]) -> Move {
+        if let Some(mv) = pv.get(1) {
+            return *mv;
+        }
+        if best_move == Move::NULL {
+            return Move::NULL;
+        }
+        let undo = board.make_move(best_move);
+        let ponder = self
+            .tt
+            .probe(board.hash(), 1)
+            .map(|entry| entry.mv)
+            .filter(|mv| board.legal_moves().contains(mv))
+            .unwrap_or(Move::NULL);
+        board.unmake_move(best_move, undo);
+        ponder
+    }
+
+    /// Returns true once a limit of the running search is reached
+    fn should_stop(&mut self) -> bool {
+        self.check_ponderhit();
+        self.stop_signaled()
+            || self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes)
+            || (!self.pondering() && self.time.out_of_time(self.nodes))
     }
-}
 
-impl Ai {
     /// Alpha beta search
     /// board: current board
-    /// depth: current depth
+    /// depth: remaining depth
+    /// ply: distance from the root
     /// alpha: lower bound
     /// beta: upper bound
-    pub fn alpha_beta(&self, board: Board, depth: usize, alpha: i32, beta: i32) -> i32 {
-        // If the depth is 0 or the game is over, return the evaluation
-        if depth == 0 || board.is_game_over() {
+    pub fn alpha_beta(
+        &mut self,
+        board: &mut Board,
+        depth: usize,
+        ply: usize,
+        alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if depth == 0 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        self.pv.clear(ply);
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+
+        if self.should_stop() {
+            self.stopped = true;
+            return 0;
+        }
+        if ply > 0 && board.state().half_move_counter() >= 100 {
+            return DRAW;
+        }
+        if ply >= MAX_PLY - 1 {
             return board.evaluate();
         }
 
-        let mut value = 0;
+        // A search leaving out the TT move must not take its result from the TT, nor store one;
+        // neither may a root search leaving out the moves of better MultiPV lines
+        let excluded = self.data.excluded(ply);
+        let partial = excluded != Move::NULL || (ply == 0 && self.pv_index > 0);
+
+        // A result of a search at least as deep may already settle this node
+        let key = board.hash();
+        let entry = self.tt.probe(key, ply);
+        if let Some(entry) = entry {
+            if ply > 0 && excluded == Move::NULL && entry.depth >= depth as i32 {
+                match entry.bound {
+                    Bound::Exact => return entry.score,
+                    Bound::Lower if entry.score >= beta => return entry.score,
+                    Bound::Upper if entry.score <= alpha => return entry.score,
+                    _ => {}
+                }
+            }
+        }
+
+        let in_check = board.is_in_check();
+        let pv_node = beta - alpha > 1;
+        let static_eval = board.evaluate();
+        let prune = ply > 0 && !pv_node && !in_check && excluded == Move::NULL;
+
+        // Reverse futility: the position is so far above beta that no reply will bring it
+        // back down
+        if prune
+            && self.params.reverse_futility
+            && depth <= REVERSE_FUTILITY_DEPTH
+            && beta.abs() < MATE_BOUND
+            && static_eval - REVERSE_FUTILITY_MARGIN * depth as i32 >= beta
+        {
+            return static_eval;
+        }
+
+        // Null move: if passing still holds beta, a real move will too. Two null moves in a
+        // row prove nothing, and without pieces zugzwang makes passing an advantage.
+        if prune
+            && self.params.null_move
+            && depth >= NULL_MOVE_DEPTH
+            && static_eval >= beta
+            && self.data.previous(ply) != Move::NULL
+            && board.has_non_pawn_material(board.state().stm())
+        {
+            self.data.play(ply, Move::NULL);
+            let undo = board.make_null_move();
+            let null_depth = null_move_depth(depth, static_eval - beta);
+            let score = -self.alpha_beta(board, null_depth, ply + 1, -beta, -beta + 1);
+            board.unmake_null_move(undo);
+
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                // A mate found after passing is not proven
+                return if score >= MATE_BOUND { beta } else { score };
+            }
+        }
+
+        let moves = if ply == 0 {
+            self.root_moves(board)
+        } else {
+            board.legal_moves()
+        };
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { DRAW };
+        }
+
+        let tt_move = entry.map_or(Move::NULL, |entry| entry.mv);
+        let picker = MovePicker::new(board, moves, &self.data, tt_move, ply);
+
         let mut alpha = alpha;
-        let mut beta = beta;
-        let moves = board.moves();
+        let mut best_score = -INFINITY;
+        let mut best_move = Move::NULL;
+        let mut quiets_tried = Vec::new();
+
+        // Futility: near the leaves, quiet moves cannot make up for a static eval far below
+        // alpha
+        let futile = prune
+            && self.params.futility
+            && depth <= FUTILITY_DEPTH
+            && alpha.abs() < MATE_BOUND
+            && static_eval + FUTILITY_MARGIN * depth as i32 <= alpha;
+
+        let extend = may_extend(ply, self.root_depth);
+        let previous = self.data.previous(ply);
+
+        for (index, mv) in picker.filter(|mv| *mv != excluded).enumerate() {
+            if ply == 0 && excluded == Move::NULL && self.verbose() {
+                self.report(Info::CurrMove {
+                    depth,
+                    mv,
+                    number: index + 1,
+                });
+            }
+            let mut extension = 0;
+
+            // Singular: if every other move fails low against a bound under the TT score, the
+            // TT move is the only good one and is searched one ply deeper
+            if let Some(entry) = entry {
+                if extend
+                    && ply > 0
+                    && mv == tt_move
+                    && excluded == Move::NULL
+                    && depth >= SINGULAR_DEPTH
+                    && matches!(entry.bound, Bound::Lower | Bound::Exact)
+                    && entry.depth + SINGULAR_TT_DEPTH as i32 >= depth as i32
+                    && entry.score.abs() < MATE_BOUND
+                {
+                    // The verification runs at the same ply and must not leave its line behind
+                    let line = self.pv.save(ply);
+                    let singular_beta = singular_beta(entry.score, depth);
+                    self.data.exclude(ply, mv);
+                    let score = self.alpha_beta(
+                        board,
+                        singular_depth(depth),
+                        ply,
+                        singular_beta - 1,
+                        singular_beta,
+                    );
+                    self.data.exclude(ply, Move::NULL);
+                    self.pv.restore(ply, &line);
+                    if self.stopped {
+                        return 0;
+                    }
+                    if score < singular_beta {
+                        extension = 1;
+                        self.stats.singular_extensions += 1;
+                    }
+                }
+            }
+
+            self.data.play(ply, mv);
+            let undo = board.make_move(mv);
+            let gives_check = board.is_in_check();
+            let quiet = mv.is_quiet() && !gives_check;
+
+            if extend && extension == 0 {
+                if gives_check {
+                    extension = 1;
+                    self.stats.check_extensions += 1;
+                } else if self.params.recapture_extension
+                    && mv.is_capture()
+                    && previous.is_capture()
+                    && previous.to == mv.to
+                {
+                    extension = 1;
+                    self.stats.recapture_extensions += 1;
+                }
+            }
+
+            if futile && index > 0 && quiet {
+                board.unmake_move(mv, undo);
+                continue;
+            }
+
+            // Principal variation search: the first move gets the full window, the others
+            // only have to prove they are no better, and are searched again if they are
+            let new_depth = depth - 1 + extension;
+            let mut score;
+            if index == 0 {
+                score = -self.alpha_beta(board, new_depth, ply + 1, -beta, -alpha);
+            } else {
+                // Late move reductions: well ordered quiet moves this late rarely matter
+                let mut reduced = 0;
+                if self.params.late_move_reductions
+                    && depth >= LMR_DEPTH
+                    && index >= LMR_MOVES
+                    && quiet
+                    && !in_check
+                {
+                    reduced = reduction(depth, index)
+                        .saturating_sub(pv_node as usize)
+                        .min(new_depth - 1);
+                }
+
+                score = -self.alpha_beta(board, new_depth - reduced, ply + 1, -alpha - 1, -alpha);
+                if score > alpha && reduced > 0 && !self.stopped {
+                    score = -self.alpha_beta(board, new_depth, ply + 1, -alpha - 1, -alpha);
+                }
+                if score > alpha && score < beta && !self.stopped {
+                    score = -self.alpha_beta(board, new_depth, ply + 1, -beta, -alpha);
+                }
+            }
+            board.unmake_move(mv, undo);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                if score > alpha {
+                    best_move = mv;
+                    self.pv.update(ply, mv);
+                    if score >= beta {
+                        if mv.is_quiet() {
+                            self.data.update_quiet(ply, depth, mv, &quiets_tried);
+                        }
+                        if !partial {
+                            self.tt
+                                .store(key, mv, score, depth as i32, Bound::Lower, ply);
+                        }
+                        return score;
+                    }
+                    alpha = score;
+                }
+            }
+            if mv.is_quiet() {
+                quiets_tried.push(mv);
+            }
+        }
+
+        if partial {
+            return best_score;
+        }
+        let bound = if best_move == Move::NULL {
+            Bound::Upper
+        } else {
+            Bound::Exact
+        };
+        self.tt
+            .store(key, best_move, best_score, depth as i32, bound, ply);
+        best_score
+    }
+}
 
-        // Iterate over all the moves and evaluate them
-        for mv in moves {
-            value = -self.alpha_beta(board, depth - 1, -beta, -alpha);
+#[cfg(test)]
+mod test {
+    use super::*;
 
-            // If this is the best move we can make, store it
-            // otherwise, prune the search
-            if value > alpha {
-                if value >= beta {
-                    return beta;
+    fn search(fen: &str, limits: Limits) -> SearchResult {
+        let mut board = Board::from_fen(fen).unwrap();
+        let result = Ai::default().search(&mut board, limits);
+        assert_eq!(board.to_fen(), fen);
+        result
+    }
+
+    fn depth(depth: usize) -> Limits {
+        Limits {
+            depth: Some(depth),
+            ..Limits::default()
+        }
+    }
+
+    #[test]
+    fn test_mate_in_one() {
+        let result = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", depth(4));
+        assert_eq!(result.best_move.to_string(), "a1a8");
+        assert_eq!(result.score, MATE - 1);
+        assert_eq!(result.pv.len(), 1);
+        // Quiescence sees the mate, and no deeper iteration finds a shorter one
+        assert_eq!(result.depth, 1);
+    }
+
+    #[test]
+    fn test_mate_in_two() {
+        // 1. Kb6 Kb8 2. Rh8# or 1. Kc7 Ka7 2. Ra1#
+        let result = search("k7/8/2K5/8/8/8/8/7R w - - 0 1", depth(6));
+        assert_eq!(result.score, MATE - 3);
+        assert_eq!(result.pv.len(), 3);
+    }
+
+    #[test]
+    fn test_aspiration_windows() {
+        // A tiny window fails again and again before the mate score fits
+        let mut board = Board::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let mut ai = Ai::default();
+        ai.params.aspiration_window = 1;
+        let result = ai.search(&mut board, depth(6));
+        assert_eq!(result.score, MATE - 3);
+
+        // Without windows the same score and move come out
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let mut narrow = Ai::default();
+        narrow.params.aspiration_window = 5;
+        let mut full = Ai::default();
+        full.params.aspiration_window = 0;
+        let mut board = Board::from_fen(fen).unwrap();
+        let narrow = narrow.search(&mut board, depth(5));
+        let full = full.search(&mut board, depth(5));
+        assert_eq!(narrow.score, full.score);
+    }
+
+    #[test]
+    fn test_selectivity_switches() {
+        // Each technique can be turned off on its own, and none of them hides the mate
+        let switches: [fn(&mut Params); 4] = [
+            |params| params.null_move = false,
+            |params| params.late_move_reductions = false,
+            |params| params.reverse_futility = false,
+            |params| params.futility = false,
+        ];
+        for switch in switches {
+            let mut ai = Ai::default();
+            switch(&mut ai.params);
+            assert_ne!(ai.params, Params::default());
+            let mut board = Board::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+            assert_eq!(ai.search(&mut board, depth(6)).score, MATE - 3);
+        }
+
+        // The selective search reaches the same depth with fewer nodes
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let mut full = Ai {
+            params: Params {
+                null_move: false,
+                late_move_reductions: false,
+                reverse_futility: false,
+                futility: false,
+                ..Params::default()
+            },
+            ..Ai::default()
+        };
+        let mut board = Board::from_fen(fen).unwrap();
+        let full = full.search(&mut board, depth(5));
+        let selective = Ai::default().search(&mut board, depth(5));
+        assert!(selective.nodes < full.nodes);
+    }
+
+    #[test]
+    fn test_extension_stats() {
+        // Checks on the way to the mate are extended, recaptures only when switched on
+        let result = search("k7/8/2K5/8/8/8/8/7R w - - 0 1", depth(6));
+        assert!(result.stats.check_extensions > 0);
+        assert_eq!(result.stats.recapture_extensions, 0);
+
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/3PP3/5N2/PPP2PPP/RNBQKB1R b KQkq - 0 3";
+        let mut board = Board::from_fen(fen).unwrap();
+        let mut ai = Ai::default();
+        ai.params.recapture_extension = true;
+        assert!(ai.search(&mut board, depth(5)).stats.recapture_extensions > 0);
+
+        // Back rank threats leave lines with a single good move deep in the tree
+        let result = search("6k1/5ppp/8/8/8/8/5PPP/3R2K1 b - - 0 1", depth(10));
+        assert!(result.stats.singular_extensions > 0);
+    }
+
+    #[test]
+    fn test_pv_with_singular_extensions() {
+        let fen = "6k1/5ppp/8/8/8/8/5PPP/3R2K1 b - - 0 1";
+        let lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        let mut ai = Ai {
+            info: Some(Box::new(move |info: &Info| {
+                if let Info::Iteration(info) = info {
+                    sink.lock().unwrap().push(info.pv.clone());
                 }
-                alpha = value;
+            })),
+            ..Ai::default()
+        };
+        let mut board = Board::from_fen(fen).unwrap();
+        let result = ai.search(&mut board, depth(10));
+        assert!(result.stats.singular_extensions > 0);
+
+        // Every line reported, not only the last, is playable
+        for pv in lines.lock().unwrap().iter() {
+            let mut board = Board::from_fen(fen).unwrap();
+            for mv in pv {
+                assert!(board.legal_moves().contains(mv), "{} in {:?}", mv, pv);
+                board.make_move(*mv);
             }
         }
+    }
+
+    #[test]
+    fn test_pv_save_restore() {
+        let mut table = PvTable::default();
+        let moves = Board::default().legal_moves();
+        table.clear(2);
+        table.update(2, moves[0]);
+        let line = table.save(2);
+
+        // A search reusing the row overwrites it
+        table.clear(2);
+        table.clear(3);
+        table.update(3, moves[2]);
+        table.update(2, moves[1]);
+        table.restore(2, &line);
+        assert_eq!(table.save(2), [moves[0]]);
+    }
+
+    #[test]
+    fn test_pv_is_legal() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let result = search(fen, depth(3));
+        assert_eq!(result.depth, 3);
+        assert_eq!(result.best_move, result.pv[0]);
+        assert!(result.seldepth >= 3);
+
+        let mut board = Board::from_fen(fen).unwrap();
+        for mv in &result.pv {
+            assert!(
+                board.legal_moves().contains(mv),
+                "{} in {:?}",
+                mv,
+                result.pv
+            );
+            board.make_move(*mv);
+        }
+    }
+
+    #[test]
+    fn test_wins_material() {
+        // The black queen on d5 is hanging to the knight
+        let result = search("4k3/8/8/3q4/8/4N3/8/4K3 w - - 0 1", depth(2));
+        assert_eq!(result.best_move.to_string(), "e3d5");
+        assert!(result.score > 200);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let limits = Limits {
+            nodes: Some(500),
+            ..Limits::default()
+        };
+        let result = search(crate::pieces::fen::START_FEN, limits);
+        assert!(result.nodes <= 500);
+        assert!(result.depth >= 1);
+        assert_ne!(result.best_move, Move::NULL);
+    }
+
+    #[test]
+    fn test_info() {
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let mut ai = Ai {
+            info: Some(Box::new(move |info: &Info| {
+                sink.lock().unwrap().push(info.clone())
+            })),
+            ..Ai::default()
+        };
+        let mut board = Board::default();
+        let result = ai.search(&mut board, depth(4));
+
+        // One exact line per iteration, then the diagnostics; a short search reports no
+        // root moves
+        let reports = reports.lock().unwrap();
+        let iterations: Vec<&IterationInfo> = reports
+            .iter()
+            .filter_map(|info| match info {
+                Info::Iteration(info) => Some(info),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            iterations.iter().map(|info| info.depth).collect::<Vec<_>>(),
+            [1, 2, 3, 4]
+        );
+        let last = iterations[3];
+        assert_eq!((last.multipv, last.bound), (1, Bound::Exact));
+        assert_eq!((last.score, &last.pv), (result.score, &result.pv));
+        assert!(last.nodes <= result.nodes && last.hashfull > 0);
+        assert!(!reports
+            .iter()
+            .any(|info| matches!(info, Info::CurrMove { .. })));
+        assert!(matches!(reports.last(), Some(Info::String(_))));
+    }
+
+    #[test]
+    fn test_multi_pv() {
+        let mut ai = Ai {
+            params: Params {
+                multi_pv: 3,
+                ..Params::default()
+            },
+            ..Ai::default()
+        };
+        let mut board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let result = ai.search(&mut board, depth(4));
+        assert_eq!(result.lines.len(), 3);
+        assert_eq!(result.lines[0].score, MATE - 1);
+        assert_eq!((result.score, &result.pv), (MATE - 1, &result.lines[0].pv));
+        // Only one move mates, the others are worse and each starts a different line
+        assert!(result.lines[1].score < MATE_BOUND);
+        assert!(result.lines[1].score >= result.lines[2].score);
+        let first: Vec<Move> = result.lines.iter().map(|line| line.pv[0]).collect();
+        assert!(first[0] != first[1] && first[0] != first[2] && first[1] != first[2]);
+
+        // No more lines than root moves
+        let mut board = Board::from_fen("7k/8/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        ai.params.multi_pv = 10;
+        let result = ai.search(&mut board, depth(2));
+        assert_eq!(result.lines.len(), 3);
+    }
+
+    #[test]
+    fn test_search_moves() {
+        let mut board = Board::default();
+        let a3 = board.legal_moves()[0];
+        let limits = Limits {
+            search_moves: vec![a3],
+            ..depth(3)
+        };
+        let result = Ai::default().search(&mut board, limits);
+        assert_eq!((result.best_move, result.pv[0]), (a3, a3));
+    }
+
+    #[test]
+    fn test_ponder() {
+        let mut ai = Ai::default();
+        let signals = Arc::clone(&ai.signals);
+        signals.ponder.store(true, Ordering::Relaxed);
+        let limits = Limits {
+            movetime: Some(Duration::from_millis(200)),
+            ponder: true,
+            ..Limits::default()
+        };
+        let search = thread::spawn(move || {
+            let mut board = Board::default();
+            ai.search(&mut board, limits)
+        });
+
+        // The clock is ignored while pondering and starts at ponderhit
+        thread::sleep(Duration::from_millis(400));
+        assert!(!search.is_finished());
+        let ponderhit = std::time::Instant::now();
+        signals.ponder.store(false, Ordering::Relaxed);
+        let result = search.join().unwrap();
+        assert!(ponderhit.elapsed() >= Duration::from_millis(100));
+        assert!(ponderhit.elapsed() < Duration::from_millis(1000));
+        assert_eq!(result.ponder_move, result.pv[1]);
+    }
+
+    #[test]
+    fn test_ponder_move() {
+        let mut ai = Ai::default();
+        let mut board = Board::default();
+        let result = ai.search(&mut board, depth(5));
+        assert_eq!(result.ponder_move, result.pv[1]);
+
+        // Without a second move in the PV the TT still knows a reply
+        let ponder = ai.ponder_move(&mut board, result.best_move, &result.pv[..1]);
+        assert_eq!(board.to_fen(), crate::pieces::fen::START_FEN);
+        board.make_move(result.best_move);
+        assert!(board.legal_moves().contains(&ponder));
+    }
+
+    #[test]
+    fn test_movetime() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let limits = Limits {
+            movetime: Some(Duration::from_millis(200)),
+            ..Limits::default()
+        };
+        let start = std::time::Instant::now();
+        let result = search(fen, limits);
+        assert!(start.elapsed() < Duration::from_millis(1000));
+        assert!(result.depth >= 1);
+        assert_ne!(result.best_move, Move::NULL);
+    }
 
-        // Return the best move
-        alpha
+    #[test]
+    fn test_no_moves() {
+        let mated = search("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", depth(3));
+        assert_eq!((mated.best_move, mated.score), (Move::NULL, -MATE));
+        let stalemate = search("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", depth(3));
+        assert_eq!((stalemate.best_move, stalemate.score), (Move::NULL, DRAW));
     }
 }
@@ -0,0 +1,267 @@
+use super::quiescence::captured;
+use super::MAX_PLY;
+use crate::pieces::board::{Board, Move, PieceType};
+
+/// History scores stay within plus or minus this bound
+pub const MAX_HISTORY: i32 = 16_384;
+
+// Score bands of the move picker, from first to last
+const TT_MOVE: i32 = 3_000_000;
+const GOOD_CAPTURE: i32 = 2_000_000;
+const KILLER: i32 = 1_000_000;
+const COUNTERMOVE: i32 = 900_000;
+const BAD_CAPTURE: i32 = -2_000_000;
+
+/// Tables learned while searching, used to order quiet moves
+///
+/// Each search thread owns one, so none of it needs to be shared.
+pub struct SearchData {
+    /// Two quiet moves per ply that recently caused a beta cutoff, newest first
+    killers: [[Move; 2]; MAX_PLY],
+    /// Butterfly history, indexed by side, from and to square
+    history: Box<[[[i32; 64]; 64]; 2]>,
+    /// Quiet move that refuted a move, indexed by its piece and target square
+    countermoves: [[Move; 64]; 12],
+    /// Move played at each ply of the current line, [`Move::NULL`] for a null move
+    played: [Move; MAX_PLY],
+    /// Move left out at each ply while checking whether the TT move is singular
+    excluded: [Move; MAX_PLY],
+}
+
+impl Default for SearchData {
+    fn default() -> Self {
+        SearchData {
+            killers: [[Move::NULL; 2]; MAX_PLY],
+            history: Box::new([[[0; 64]; 64]; 2]),
+            countermoves: [[Move::NULL; 64]; 12],
+            played: [Move::NULL; MAX_PLY],
+            excluded: [Move::NULL; MAX_PLY],
+        }
+    }
+}
+
+impl SearchData {
+    /// Forgets everything, as before a new game
+    pub fn clear(&mut self) {
+        *self = SearchData::default();
+    }
+
+    /// Prepares for a new search: killers belong to the old root, and history is halved so
+    /// that recent results weigh more
+    pub fn new_search(&mut self) {
+        self.killers = [[Move::NULL; 2]; MAX_PLY];
+        self.history
+            .iter_mut()
+            .flatten()
+            .flatten()
+            .for_each(|score| *score /= 2);
+    }
+
+    /// Records the move about to be searched at `ply`
+    pub fn play(&mut self, ply: usize, mv: Move) {
+        self.played[ply] = mv;
+    }
+
+    /// Returns the move that led to the node at `ply`, [`Move::NULL`] at the root
+    pub fn previous(&self, ply: usize) -> Move {
+        match ply {
+            0 => Move::NULL,
+            _ => self.played[ply - 1],
+        }
+    }
+
+    /// Leaves `mv` out of the search of `ply`, [`Move::NULL`] to leave nothing out
+    pub fn exclude(&mut self, ply: usize, mv: Move) {
+        self.excluded[ply] = mv;
+    }
+
+    /// Returns the move left out of the search of `ply`
+    pub fn excluded(&self, ply: usize) -> Move {
+        self.excluded[ply]
+    }
+
+    /// Returns the killer moves of a ply
+    pub fn killers(&self, ply: usize) -> [Move; 2] {
+        self.killers[ply]
+    }
+
+    /// Returns the history score of a quiet move
+    pub fn history(&self, mv: Move) -> i32 {
+        self.history[mv.side().index()][mv.from][mv.to]
+    }
+
+    /// Returns the quiet move that last refuted `previous`
+    pub fn countermove(&self, previous: Move) -> Move {
+        match previous {
+            Move::NULL => Move::NULL,
+            _ => self.countermoves[counter_index(previous)][previous.to],
+        }
+    }
+
+    /// Learns from a quiet move causing a beta cutoff at `ply`: it becomes a killer and the
+    /// countermove of the previous move, its history grows and the history of the quiet
+    /// moves searched before it shrinks
+    pub fn update_quiet(&mut self, ply: usize, depth: usize, mv: Move, tried: &[Move]) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != mv {
+            killers[1] = killers[0];
+            killers[0] = mv;
+        }
+
+        let previous = self.previous(ply);
+        if previous != Move::NULL {
+            self.countermoves[counter_index(previous)][previous.to] = mv;
+        }
+
+        let bonus = (depth * depth).min(MAX_HISTORY as usize / 4) as i32;
+        self.add_history(mv, bonus);
+        for &other in tried {
+            self.add_history(other, -bonus);
+        }
+    }
+
+    /// Moves a history score towards the bound in the direction of `bonus`, more slowly the
+    /// closer it already is
+    fn add_history(&mut self, mv: Move, bonus: i32) {
+        let score = &mut self.history[mv.side().index()][mv.from][mv.to];
+        *score += bonus - *score * bonus.abs() / MAX_HISTORY;
+    }
+}
+
+/// Returns the countermove table row of the piece making a move
+fn counter_index(mv: Move) -> usize {
+    6 * mv.side().index() + mv.piece().index()
+}
+
+/// Returns the MVV-LVA score of a capture or promotion: most valuable victim first, least
+/// valuable attacker among equal victims
+pub fn mvv_lva(board: &Board, mv: Move) -> i32 {
+    let victim = captured(board, mv).map_or(0, PieceType::value);
+    let promotion = mv.promotion().map_or(0, PieceType::value);
+    10 * (victim + promotion) - mv.piece().value()
+}
+
+/// Hands out the legal moves of a node, most promising first
+///
+/// The hash move comes first, then captures and promotions that do not lose material by
+/// MVV-LVA, then the killers of the ply, then the countermove of the previous move, then the
+/// other quiet moves by history and finally the losing captures. Moves are scored up front
+/// and picked one at a time, so a node that cuts off early never sorts the rest.
+pub struct MovePicker {
+    moves: Vec<Move>,
+    scores: Vec<i32>,
+}
+
+impl MovePicker {
+    /// Scores the legal moves of `board`
+    pub fn new(
+        board: &Board,
+        moves: Vec<Move>,
+        data: &SearchData,
+        tt_move: Move,
+        ply: usize,
+    ) -> Self {
+        let killers = data.killers(ply);
+        let countermove = data.countermove(data.previous(ply));
+        let scores = moves
+            .iter()
+            .map(|&mv| {
+                if mv == tt_move {
+                    TT_MOVE
+                } else if !mv.is_quiet() {
+                    let base = if board.see_ge(mv, 0) {
+                        GOOD_CAPTURE
+                    } else {
+                        BAD_CAPTURE
+                    };
+                    base + mvv_lva(board, mv)
+                } else if mv == killers[0] {
+                    KILLER + 1
+                } else if mv == killers[1] {
+                    KILLER
+                } else if mv == countermove {
+                    COUNTERMOVE
+                } else {
+                    data.history(mv)
+                }
+            })
+            .collect();
+        MovePicker { moves, scores }
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        let best = (0..self.moves.len()).max_by_key(|&index| self.scores[index])?;
+        self.scores.swap_remove(best);
+        Some(self.moves.swap_remove(best))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn find(board: &Board, name: &str) -> Move {
+        *board
+            .legal_moves()
+            .iter()
+            .find(|mv| mv.to_string() == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_picker_order() {
+        let board = Board::from_fen("4k3/2p5/3p1n2/4P3/8/8/8/3QK3 w - - 0 1").unwrap();
+        let mut data = SearchData::default();
+        data.killers[0] = [find(&board, "d1h5"), find(&board, "d1a4")];
+        data.add_history(find(&board, "d1d4"), 100);
+        data.add_history(find(&board, "e1f1"), -100);
+
+        let tt_move = find(&board, "e1d2");
+        let picker = MovePicker::new(&board, board.legal_moves(), &data, tt_move, 0);
+        let names: Vec<String> = picker.map(|mv| mv.to_string()).collect();
+        assert_eq!(names.len(), board.legal_moves().len());
+        assert_eq!(names[..5], ["e1d2", "e5f6", "e5d6", "d1h5", "d1a4"]);
+        assert_eq!(names[5], "d1d4");
+        // Behind every quiet move without history
+        assert_eq!(names[names.len() - 2], "e1f1");
+        // Taking the defended pawn loses the queen
+        assert_eq!(names[names.len() - 1], "d1d6");
+    }
+
+    #[test]
+    fn test_update_quiet() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let first = find(&board, "a1a7");
+        let second = find(&board, "a1a8");
+        let tried = find(&board, "e1d1");
+        let previous = Move::new(52, 60, crate::pieces::board::BoardFlags::king);
+
+        let mut data = SearchData::default();
+        data.play(0, previous);
+        data.update_quiet(1, 4, first, &[tried]);
+        data.update_quiet(1, 4, second, &[]);
+        assert_eq!(data.killers(1), [second, first]);
+        // The same killer is not stored twice
+        data.update_quiet(1, 4, second, &[]);
+        assert_eq!(data.killers(1), [second, first]);
+        assert_eq!(data.countermove(previous), second);
+        assert!(data.history(first) > 0);
+        assert!(data.history(tried) < 0);
+
+        // History never leaves its bounds
+        for _ in 0..1000 {
+            data.update_quiet(2, 100, first, &[]);
+        }
+        assert!(data.history(first) <= MAX_HISTORY);
+
+        data.new_search();
+        assert_eq!(data.killers(1), [Move::NULL; 2]);
+        assert_eq!(data.countermove(previous), second);
+        data.clear();
+        assert_eq!(data.history(first), 0);
+    }
+}
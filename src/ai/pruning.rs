@@ -0,0 +1,79 @@
+use super::MAX_PLY;
+use std::sync::LazyLock;
+
+/// Shallowest depth null move pruning is tried at
+pub const NULL_MOVE_DEPTH: usize = 3;
+/// Plies a null move search is shallower by, before adapting to depth and eval
+pub const NULL_MOVE_REDUCTION: usize = 3;
+
+/// Deepest node reverse futility pruning applies to
+pub const REVERSE_FUTILITY_DEPTH: usize = 6;
+/// Margin per ply of depth the static eval must beat beta by
+pub const REVERSE_FUTILITY_MARGIN: i32 = 80;
+
+/// Deepest node futility pruning applies to
+pub const FUTILITY_DEPTH: usize = 3;
+/// Margin per ply of depth a quiet move could plausibly gain
+pub const FUTILITY_MARGIN: i32 = 120;
+
+/// Shallowest depth late move reductions apply at
+pub const LMR_DEPTH: usize = 3;
+/// Moves searched at full depth before the others are reduced
+pub const LMR_MOVES: usize = 3;
+
+/// Most moves in a position the reduction table has a column for
+const LMR_MAX_MOVES: usize = 64;
+
+/// Late move reductions by depth and move number, growing with the logarithm of both
+static REDUCTIONS: LazyLock<[[usize; LMR_MAX_MOVES]; MAX_PLY]> = LazyLock::new(|| {
+    let mut table = [[0; LMR_MAX_MOVES]; MAX_PLY];
+    for (depth, row) in table.iter_mut().enumerate().skip(1) {
+        for (moves, reduction) in row.iter_mut().enumerate().skip(1) {
+            *reduction = (0.75 + (depth as f64).ln() * (moves as f64).ln() / 2.25) as usize;
+        }
+    }
+    table
+});
+
+/// Returns how many plies to reduce the `moves`-th move searched at `depth`
+pub fn reduction(depth: usize, moves: usize) -> usize {
+    REDUCTIONS[depth.min(MAX_PLY - 1)][moves.min(LMR_MAX_MOVES - 1)]
+}
+
+/// Returns the depth a null move is searched with: deeper nodes and positions far above
+/// beta are reduced more
+pub fn null_move_depth(depth: usize, eval_margin: i32) -> usize {
+    let reduction = NULL_MOVE_REDUCTION + depth / 4 + (eval_margin / 200).clamp(0, 3) as usize;
+    depth.saturating_sub(1 + reduction)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reductions() {
+        // The first moves and shallow depths are not reduced
+        assert_eq!(reduction(1, 1), 0);
+        assert_eq!(reduction(LMR_DEPTH, LMR_MOVES), 1);
+        for depth in 1..MAX_PLY {
+            for moves in 1..LMR_MAX_MOVES {
+                assert!(reduction(depth, moves) <= reduction(depth, moves + 1));
+                assert!(reduction(depth, moves) <= reduction(depth + 1, moves));
+                assert!(reduction(depth, moves) <= depth);
+            }
+        }
+        assert_eq!(
+            reduction(1000, 1000),
+            reduction(MAX_PLY - 1, LMR_MAX_MOVES - 1)
+        );
+    }
+
+    #[test]
+    fn test_null_move_depth() {
+        assert_eq!(null_move_depth(3, 0), 0);
+        assert_eq!(null_move_depth(8, 0), 2);
+        assert_eq!(null_move_depth(8, 450), 0);
+        assert_eq!(null_move_depth(12, 10_000), 2);
+    }
+}
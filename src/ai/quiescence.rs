@@ -0,0 +1,157 @@
+use super::ordering::mvv_lva;
+use super::{Ai, MATE, MAX_PLY};
+use crate::pieces::board::{Board, Move, PieceType};
+use crate::pieces::movegen::{MoveGen, MoveList, Stage};
+
+/// Margin added to a capture's gain before delta pruning gives up on it
+pub const DELTA_MARGIN: i32 = 200;
+
+/// Returns the piece a move takes, if any
+pub fn captured(board: &Board, mv: Move) -> Option<PieceType> {
+    if mv.is_en_passant() {
+        Some(PieceType::Pawn)
+    } else if mv.is_capture() {
+        board.piece_on(mv.to).map(|(_, piece)| piece)
+    } else {
+        None
+    }
+}
+
+/// Sorts captures by most valuable victim, then least valuable attacker
+fn order_captures(board: &Board, moves: &mut [Move]) {
+    moves.sort_by_key(|mv| -mvv_lva(board, *mv));
+}
+
+impl Ai {
+    /// Searches captures and promotions until the position is quiet, or every evasion when
+    /// in check, so that leaf scores do not miss pieces left hanging
+    pub fn quiescence(&mut self, board: &mut Board, ply: usize, alpha: i32, beta: i32) -> i32 {
+        self.pv.clear(ply);
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+
+        if self.should_stop() {
+            self.stopped = true;
+            return 0;
+        }
+
+        let in_check = board.is_in_check();
+        if ply >= MAX_PLY - 1 {
+            return if in_check { 0 } else { board.evaluate() };
+        }
+
+        // Standing pat: the side to move can usually do at least as well as the static score
+        // by not capturing, which is not an option when in check
+        let mut alpha = alpha;
+        let mut best_score = -MATE + ply as i32;
+        let stand_pat = board.evaluate();
+        if !in_check {
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+            best_score = stand_pat;
+        }
+
+        let mut moves = MoveList::new();
+        let stage = if in_check {
+            Stage::Evasions
+        } else {
+            Stage::Captures
+        };
+        MoveGen::new(board).generate(stage, &mut moves);
+        order_captures(board, &mut moves);
+
+        for &mv in moves.iter() {
+            if !in_check {
+                // Underpromotions are almost never better than a queen
+                if mv
+                    .promotion()
+                    .is_some_and(|piece| piece != PieceType::Queen)
+                {
+                    continue;
+                }
+                // Delta pruning: skip captures that cannot raise alpha even with a margin
+                let gain = captured(board, mv).map_or(0, PieceType::value)
+                    + mv.promotion()
+                        .map_or(0, |piece| piece.value() - PieceType::Pawn.value());
+                if stand_pat + gain + DELTA_MARGIN <= alpha {
+                    continue;
+                }
+                // Captures that lose material once the exchange is played out
+                if !board.see_ge(mv, 0) {
+                    continue;
+                }
+            }
+            if !board.is_legal(mv) {
+                continue;
+            }
+
+            let undo = board.make_move(mv);
+            let score = -self.quiescence(board, ply + 1, -beta, -alpha);
+            board.unmake_move(mv, undo);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                if score > alpha {
+                    self.pv.update(ply, mv);
+                    if score >= beta {
+                        break;
+                    }
+                    alpha = score;
+                }
+            }
+        }
+
+        best_score
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ai::INFINITY;
+
+    fn quiescence(fen: &str) -> i32 {
+        let mut board = Board::from_fen(fen).unwrap();
+        let score = Ai::default().quiescence(&mut board, 0, -INFINITY, INFINITY);
+        assert_eq!(board.to_fen(), fen);
+        score
+    }
+
+    #[test]
+    fn test_hanging_piece() {
+        // The knight takes the queen, which the static evaluation alone misses
+        let fen = "4k3/8/8/3q4/8/4N3/8/4K3 w - - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        assert!(quiescence(fen) > board.evaluate() + 800);
+    }
+
+    #[test]
+    fn test_defended_piece() {
+        // Taking the defended pawn loses the queen, so standing pat is best
+        let fen = "4k3/2p5/3p4/8/8/8/8/3QK3 w - - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(quiescence(fen), board.evaluate());
+    }
+
+    #[test]
+    fn test_mated_in_check() {
+        // In check with no evasion there is no standing pat
+        assert_eq!(quiescence("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1"), -MATE);
+    }
+
+    #[test]
+    fn test_order_captures() {
+        let board = Board::from_fen("4k3/8/2r1q3/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+        let mut moves = MoveList::new();
+        MoveGen::new(&board).generate(Stage::Captures, &mut moves);
+        order_captures(&board, &mut moves);
+        let names: Vec<String> = moves.iter().map(|mv| mv.to_string()).collect();
+        assert_eq!(names, ["d5e6", "d5c6"]);
+    }
+}
@@ -0,0 +1,210 @@
+use super::Limits;
+use crate::pieces::board::Side;
+use std::time::{Duration, Instant};
+
+/// Default time kept back for the GUI and the connection
+pub const DEFAULT_MOVE_OVERHEAD: Duration = Duration::from_millis(10);
+/// Largest move overhead that can be set
+pub const MAX_MOVE_OVERHEAD: Duration = Duration::from_millis(5000);
+
+/// Moves the remaining time is spread over when the GUI does not say
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// Most moves the remaining time is spread over, even if the GUI asks for more
+const MAX_MOVES_TO_GO: u32 = 50;
+/// Score drop in centipawns since the last iteration that calls for more time
+const SCORE_DROP: i32 = 30;
+/// Nodes searched between two looks at the clock
+const CHECK_INTERVAL: u64 = 1024;
+
+/// Decides how long a search may take
+///
+/// The soft limit is the time an iteration should not start past, scaled by how settled the
+/// search looks: a best move that keeps coming back shrinks it, a falling score stretches it.
+/// The hard limit stops the search in the middle of an iteration.
+#[derive(Debug, Clone)]
+pub struct TimeManager {
+    start: Instant,
+    soft: Option<Duration>,
+    hard: Option<Duration>,
+}
+
+impl Default for TimeManager {
+    fn default() -> Self {
+        TimeManager {
+            start: Instant::now(),
+            soft: None,
+            hard: None,
+        }
+    }
+}
+
+impl TimeManager {
+    /// Starts the clock for a search by `side` under `limits`, keeping `overhead` back from
+    /// every limit for the GUI
+    ///
+    /// A pondering search gets the limits it will have once the opponent plays the expected
+    /// move; the search does not look at them until then.
+    pub fn new(limits: &Limits, side: Side, overhead: Duration) -> Self {
+        let mut manager = TimeManager::default();
+        if limits.infinite {
+            return manager;
+        }
+
+        if let Some(movetime) = limits.movetime {
+            let time = movetime.saturating_sub(overhead);
+            manager.soft = Some(time);
+            manager.hard = Some(time);
+            return manager;
+        }
+
+        let (time, increment) = match side {
+            Side::White => (limits.wtime, limits.winc),
+            Side::Black => (limits.btime, limits.binc),
+        };
+        if let Some(time) = time {
+            let time = time.saturating_sub(overhead);
+            let increment = increment.unwrap_or_default();
+            let moves = limits
+                .movestogo
+                .unwrap_or(DEFAULT_MOVES_TO_GO)
+                .clamp(1, MAX_MOVES_TO_GO);
+
+            let hard = (time / moves * 4 + increment).min(time * 3 / 4);
+            let soft = (time / moves + increment * 3 / 4).min(hard);
+            manager.soft = Some(soft);
+            manager.hard = Some(hard);
+        }
+        manager
+    }
+
+    /// Starts the clock over, keeping the limits
+    pub fn restart(&mut self) {
+        self.start = Instant::now();
+    }
+
+    /// Returns the time since the search started
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Returns the soft limit
+    pub fn soft(&self) -> Option<Duration> {
+        self.soft
+    }
+
+    /// Returns the hard limit
+    pub fn hard(&self) -> Option<Duration> {
+        self.hard
+    }
+
+    /// Returns true once the hard limit is reached, only looking at the clock every few
+    /// thousand nodes
+    pub fn out_of_time(&self, nodes: u64) -> bool {
+        nodes.is_multiple_of(CHECK_INTERVAL) && self.hard.is_some_and(|hard| self.elapsed() >= hard)
+    }
+
+    /// Returns true if no new iteration should start, given how many iterations in a row
+    /// found the same best move and how far the score fell in the last one
+    pub fn stop_iterating(&self, stability: usize, score_drop: i32) -> bool {
+        let Some(soft) = self.soft else {
+            return false;
+        };
+
+        // From 1.3 for a new best move down to 0.6 once it has held for 7 iterations
+        let mut scale = 1.3 - 0.1 * stability.min(7) as f64;
+        if score_drop >= SCORE_DROP {
+            scale *= if score_drop >= 3 * SCORE_DROP {
+                2.0
+            } else {
+                1.5
+            };
+        }
+
+        let limit = soft.mul_f64(scale).min(self.hard.unwrap_or(soft));
+        self.elapsed() >= limit
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn millis(ms: u64) -> Option<Duration> {
+        Some(Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn test_limits() {
+        let overhead = Duration::from_millis(10);
+
+        let limits = Limits {
+            movetime: millis(1000),
+            ..Limits::default()
+        };
+        let manager = TimeManager::new(&limits, Side::White, overhead);
+        assert_eq!((manager.soft(), manager.hard()), (millis(990), millis(990)));
+
+        // Only the clock of the side to move counts
+        let limits = Limits {
+            wtime: millis(60_010),
+            btime: millis(3_010),
+            winc: millis(1000),
+            ..Limits::default()
+        };
+        let manager = TimeManager::new(&limits, Side::White, overhead);
+        assert_eq!(manager.soft(), millis(2750));
+        assert_eq!(manager.hard(), millis(9000));
+        let manager = TimeManager::new(&limits, Side::Black, overhead);
+        assert_eq!(manager.hard(), millis(400));
+
+        // The last move before the time control may use most of the clock, but never all
+        let limits = Limits {
+            wtime: millis(10_010),
+            movestogo: Some(1),
+            ..Limits::default()
+        };
+        let manager = TimeManager::new(&limits, Side::White, overhead);
+        assert_eq!(
+            (manager.soft(), manager.hard()),
+            (millis(7500), millis(7500))
+        );
+
+        for limits in [
+            Limits::default(),
+            Limits {
+                infinite: true,
+                movetime: millis(5),
+                ..Limits::default()
+            },
+        ] {
+            let manager = TimeManager::new(&limits, Side::White, overhead);
+            assert_eq!((manager.soft(), manager.hard()), (None, None));
+            assert!(!manager.stop_iterating(0, 0));
+            assert!(!manager.out_of_time(0));
+        }
+    }
+
+    #[test]
+    fn test_scaling() {
+        let mut manager = TimeManager {
+            start: Instant::now() - Duration::from_millis(1000),
+            soft: millis(1000),
+            hard: millis(10_000),
+        };
+        // A settled best move stops early, a new one or a falling score gets more time
+        assert!(manager.stop_iterating(7, 0));
+        assert!(!manager.stop_iterating(0, 0));
+        assert!(!manager.stop_iterating(7, 3 * SCORE_DROP));
+
+        // Never past the hard limit
+        manager.hard = millis(1000);
+        assert!(manager.stop_iterating(0, 10 * SCORE_DROP));
+        assert!(manager.out_of_time(CHECK_INTERVAL));
+        assert!(!manager.out_of_time(CHECK_INTERVAL + 1));
+
+        // The time spent before a restart does not count
+        manager.restart();
+        assert!(!manager.stop_iterating(0, 10 * SCORE_DROP));
+        assert!(!manager.out_of_time(CHECK_INTERVAL));
+    }
+}
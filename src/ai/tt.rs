@@ -0,0 +1,284 @@
+use super::MATE_BOUND;
+use crate::pieces::board::Move;
+
+/// Default table size in megabytes
+pub const DEFAULT_HASH_MB: usize = 16;
+/// Largest table size in megabytes
+pub const MAX_HASH_MB: usize = 65536;
+
+/// How a stored score relates to the true score of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bound {
+    /// The slot is empty
+    #[default]
+    None,
+    /// The score is exact
+    Exact,
+    /// The search failed high, the true score is at least the stored one
+    Lower,
+    /// The search failed low, the true score is at most the stored one
+    Upper,
+}
+
+/// What is remembered about a searched position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// Full Zobrist hash, checked on probe
+    pub key: u64,
+    /// Best move found, [`Move::NULL`] after a fail low
+    pub mv: Move,
+    /// Score relative to the position, mate scores counted from here
+    pub score: i32,
+    /// Remaining depth of the search that stored the entry
+    pub depth: i32,
+    pub bound: Bound,
+    /// Search number the entry was written in
+    pub generation: u8,
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Entry {
+            key: 0,
+            mv: Move::NULL,
+            score: 0,
+            depth: 0,
+            bound: Bound::None,
+            generation: 0,
+        }
+    }
+}
+
+/// Two entries sharing an index: the first keeps the deepest result of the current search,
+/// the second always takes what the first refused
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    depth_preferred: Entry,
+    always_replace: Entry,
+}
+
+/// Hash table of search results, indexed by the low bits of the Zobrist hash
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    generation: u8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_HASH_MB)
+    }
+}
+
+/// Makes a mate score relative to the node storing it instead of the root
+pub fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Makes a stored mate score relative to the root again
+pub fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+impl TranspositionTable {
+    /// Creates a table of at most `megabytes` megabytes
+    pub fn new(megabytes: usize) -> Self {
+        let mut table = TranspositionTable {
+            buckets: Vec::new(),
+            generation: 0,
+        };
+        table.resize(megabytes);
+        table
+    }
+
+    /// Reallocates the table with the largest power of two buckets fitting in `megabytes`,
+    /// dropping every entry
+    pub fn resize(&mut self, megabytes: usize) {
+        let bytes = megabytes.clamp(1, MAX_HASH_MB) << 20;
+        let buckets = bytes / std::mem::size_of::<Bucket>();
+        let buckets = 1 << (usize::BITS - 1 - buckets.leading_zeros());
+        self.buckets = vec![Bucket::default(); buckets];
+        self.generation = 0;
+    }
+
+    /// Empties every bucket
+    pub fn clear(&mut self) {
+        self.buckets.fill(Bucket::default());
+        self.generation = 0;
+    }
+
+    /// Returns the number of buckets
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Returns true if the table has no buckets, which never happens after [`Self::new`]
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Starts a new search, making older entries the first to be replaced
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    fn bucket(&self, key: u64) -> usize {
+        key as usize & (self.buckets.len() - 1)
+    }
+
+    /// Returns the entry of a position with its score made relative to the root, `ply` plies
+    /// away
+    pub fn probe(&self, key: u64, ply: usize) -> Option<Entry> {
+        let bucket = &self.buckets[self.bucket(key)];
+        [bucket.depth_preferred, bucket.always_replace]
+            .into_iter()
+            .find(|entry| entry.bound != Bound::None && entry.key == key)
+            .map(|entry| Entry {
+                score: score_from_tt(entry.score, ply),
+                ..entry
+            })
+    }
+
+    /// Stores a search result found `ply` plies from the root
+    pub fn store(&mut self, key: u64, mv: Move, score: i32, depth: i32, bound: Bound, ply: usize) {
+        let generation = self.generation;
+        let index = self.bucket(key);
+        let bucket = &mut self.buckets[index];
+
+        let mut entry = Entry {
+            key,
+            mv,
+            score: score_to_tt(score, ply),
+            depth,
+            bound,
+            generation,
+        };
+
+        let preferred = &bucket.depth_preferred;
+        if preferred.key == key
+            || preferred.bound == Bound::None
+            || preferred.generation != generation
+            || depth >= preferred.depth
+        {
+            // Keep the old best move of the position if the new result has none
+            if entry.mv == Move::NULL && preferred.key == key {
+                entry.mv = preferred.mv;
+            }
+            bucket.depth_preferred = entry;
+        } else {
+            if entry.mv == Move::NULL && bucket.always_replace.key == key {
+                entry.mv = bucket.always_replace.mv;
+            }
+            bucket.always_replace = entry;
+        }
+    }
+
+    /// Returns how full the table is in permille, counting entries of the current search in
+    /// the first thousand buckets
+    pub fn hashfull(&self) -> usize {
+        let sample = &self.buckets[..self.buckets.len().min(1000)];
+        let used = sample
+            .iter()
+            .flat_map(|bucket| [bucket.depth_preferred, bucket.always_replace])
+            .filter(|entry| entry.bound != Bound::None && entry.generation == self.generation)
+            .count();
+        used * 1000 / (2 * sample.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ai::MATE;
+    use crate::pieces::board::{BoardFlags, E2, E4};
+
+    #[test]
+    fn test_size() {
+        let table = TranspositionTable::new(1);
+        assert!(table.len().is_power_of_two());
+        assert!(table.len() * std::mem::size_of::<Bucket>() <= 1 << 20);
+        assert!(2 * table.len() * std::mem::size_of::<Bucket>() > 1 << 20);
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let mut table = TranspositionTable::new(1);
+        let mv = Move::new(E2, E4, BoardFlags::pawn | BoardFlags::white);
+        let key = 0x1234_5678_9abc_def0;
+        assert_eq!(table.probe(key, 0), None);
+
+        table.store(key, mv, 35, 6, Bound::Exact, 3);
+        let entry = table.probe(key, 0).unwrap();
+        assert_eq!((entry.mv, entry.score, entry.depth), (mv, 35, 6));
+        assert_eq!(entry.bound, Bound::Exact);
+        // Another key in the same bucket does not match
+        assert_eq!(table.probe(key ^ (1 << 63), 0), None);
+
+        // A fail low keeps the old move
+        table.store(key, Move::NULL, -10, 7, Bound::Upper, 0);
+        assert_eq!(table.probe(key, 0).unwrap().mv, mv);
+    }
+
+    #[test]
+    fn test_replacement() {
+        let mut table = TranspositionTable::new(1);
+        // High bits only, so all three share bucket 0
+        let deep = 1 << 60;
+        let shallow = 1 << 61;
+        let newer = 1 << 62;
+
+        table.store(deep, Move::NULL, 0, 10, Bound::Lower, 0);
+        // Shallower entries of the same search go to the always-replace slot
+        table.store(shallow, Move::NULL, 0, 2, Bound::Lower, 0);
+        table.store(newer, Move::NULL, 0, 3, Bound::Lower, 0);
+        assert!(table.probe(deep, 0).is_some());
+        assert!(table.probe(shallow, 0).is_none());
+        assert!(table.probe(newer, 0).is_some());
+
+        // Entries of an older search give way to anything
+        table.new_search();
+        table.store(shallow, Move::NULL, 0, 1, Bound::Lower, 0);
+        assert!(table.probe(deep, 0).is_none());
+        assert!(table.probe(shallow, 0).is_some());
+    }
+
+    #[test]
+    fn test_mate_scores() {
+        let mut table = TranspositionTable::new(1);
+        // Mate in 5 plies from the root, found 3 plies deep, is a mate in 2 from the node
+        table.store(1, Move::NULL, MATE - 5, 4, Bound::Exact, 3);
+        assert_eq!(table.buckets[1].depth_preferred.score, MATE - 2);
+        assert_eq!(table.probe(1, 3).unwrap().score, MATE - 5);
+        // Reached 7 plies from the root the same mate is 2 plies further away
+        assert_eq!(table.probe(1, 7).unwrap().score, MATE - 9);
+
+        table.store(2, Move::NULL, -MATE + 6, 4, Bound::Exact, 2);
+        assert_eq!(table.probe(2, 4).unwrap().score, -MATE + 8);
+        assert_eq!(score_to_tt(120, 9), 120);
+    }
+
+    #[test]
+    fn test_hashfull() {
+        let mut table = TranspositionTable::new(1);
+        assert_eq!(table.hashfull(), 0);
+        for key in 0..1000 {
+            table.store(key, Move::NULL, 0, 1, Bound::Exact, 0);
+        }
+        assert_eq!(table.hashfull(), 500);
+        table.new_search();
+        assert_eq!(table.hashfull(), 0);
+        table.clear();
+        assert!(table.probe(5, 0).is_none());
+    }
+}
@@ -0,0 +1,294 @@
+//! Polyglot opening books
+//!
+//! A book is a file of 16 byte big-endian entries sorted by position key: the key, the
+//! move, its weight and 4 bytes of learning data that are ignored here.
+
+mod keys;
+
+use crate::pieces::attacks::pawn_attacks;
+use crate::pieces::board::{Board, Move, PieceType, Side, E1, E8, POSITION_ARRAY};
+use crate::pieces::fen::square_name;
+use crate::Castling;
+use keys::{CASTLING_OFFSET, EN_PASSANT_OFFSET, RANDOM64, TURN_OFFSET};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Size of one book entry in bytes
+const ENTRY_SIZE: u64 = 16;
+
+/// Returns the Polyglot key of a position
+///
+/// Unlike [`Board::hash`] the en passant file only counts when a pawn can actually capture
+/// en passant, and the turn key is xored in for white.
+///
+/// Example
+/// ```
+/// use rust_chess::book::polyglot_key;
+/// use rust_chess::pieces::board::Board;
+///
+/// assert_eq!(polyglot_key(&Board::default()), 0x463b96181691fc9c);
+/// ```
+pub fn polyglot_key(board: &Board) -> u64 {
+    let mut key = 0;
+    for side in [Side::White, Side::Black] {
+        for piece in PieceType::ALL {
+            let kind = 2 * piece.index() + (side == Side::White) as usize;
+            let mut set = board.pieces(side, piece);
+            while set != 0 {
+                key ^= RANDOM64[64 * kind + set.trailing_zeros() as usize];
+                set &= set - 1;
+            }
+        }
+    }
+
+    let state = board.state();
+    for (i, right) in [
+        Castling::WHITE_00,
+        Castling::WHITE_000,
+        Castling::BLACK_00,
+        Castling::BLACK_000,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        if state.castling_rights().contains(right) {
+            key ^= RANDOM64[CASTLING_OFFSET + i];
+        }
+    }
+
+    if let Some(square) = state.en_passant_square() {
+        let us = state.stm();
+        // Our pawns that could capture are the ones an enemy pawn on the square would attack
+        if pawn_attacks(us.opposite(), square) & board.pieces(us, PieceType::Pawn) != 0 {
+            key ^= RANDOM64[EN_PASSANT_OFFSET + square % 8];
+        }
+    }
+
+    if state.stm() == Side::White {
+        key ^= RANDOM64[TURN_OFFSET];
+    }
+    key
+}
+
+/// A move found in the book with its weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookMove {
+    pub mv: Move,
+    pub weight: u16,
+}
+
+/// An open Polyglot book
+pub struct Book {
+    file: File,
+    entries: u64,
+    /// State of the xorshift generator picking among weighted moves
+    seed: u64,
+}
+
+impl Book {
+    /// Opens a Polyglot `.bin` file
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Book> {
+        let file = File::open(path)?;
+        let entries = file.metadata()?.len() / ENTRY_SIZE;
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Ok(Book {
+            file,
+            entries,
+            seed: seed | 1,
+        })
+    }
+
+    /// Reads the key, move and weight of the entry at `index`
+    fn entry(&mut self, index: u64) -> io::Result<(u64, u16, u16)> {
+        let mut bytes = [0; ENTRY_SIZE as usize];
+        self.file.seek(SeekFrom::Start(index * ENTRY_SIZE))?;
+        self.file.read_exact(&mut bytes)?;
+        Ok((
+            u64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+            u16::from_be_bytes(bytes[8..10].try_into().unwrap()),
+            u16::from_be_bytes(bytes[10..12].try_into().unwrap()),
+        ))
+    }
+
+    /// Returns the legal book moves of the position, highest weight first
+    pub fn probe(&mut self, board: &Board) -> io::Result<Vec<BookMove>> {
+        let key = polyglot_key(board);
+
+        // Binary search the first entry with the key
+        let (mut low, mut high) = (0, self.entries);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.entry(middle)?.0 < key {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        let legal = board.legal_moves();
+        let mut moves = Vec::new();
+        for index in low..self.entries {
+            let (entry_key, raw, weight) = self.entry(index)?;
+            if entry_key != key {
+                break;
+            }
+            let name = decode_move(board, raw);
+            if let Some(mv) = legal.iter().find(|mv| mv.to_string() == name) {
+                moves.push(BookMove { mv: *mv, weight });
+            }
+        }
+        moves.sort_by_key(|book_move| std::cmp::Reverse(book_move.weight));
+        Ok(moves)
+    }
+
+    /// Picks a book move at random, each move with a chance proportional to its weight
+    pub fn pick(&mut self, board: &Board) -> io::Result<Option<Move>> {
+        let moves = self.probe(board)?;
+        let total: u64 = moves.iter().map(|book_move| book_move.weight as u64).sum();
+        if total == 0 {
+            return Ok(moves.first().map(|book_move| book_move.mv));
+        }
+
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        let mut choice = self.seed % total;
+        for book_move in &moves {
+            if choice < book_move.weight as u64 {
+                return Ok(Some(book_move.mv));
+            }
+            choice -= book_move.weight as u64;
+        }
+        unreachable!("choice is below the total weight")
+    }
+}
+
+/// Turns a Polyglot move into UCI notation
+///
+/// Polyglot stores castling as the king taking its own rook, which is rewritten to the
+/// king's real target square.
+fn decode_move(board: &Board, raw: u16) -> String {
+    let to = (raw & 0x3f) as usize;
+    let from = ((raw >> 6) & 0x3f) as usize;
+    let promotion = ["", "n", "b", "r", "q"]
+        .get(((raw >> 12) & 0x7) as usize)
+        .unwrap_or(&"");
+
+    let king = board.pieces(board.state().stm(), PieceType::King) & POSITION_ARRAY[from] != 0;
+    let to = match (from, to) {
+        (E1, 7) | (E8, 63) if king => to - 1,
+        (E1, 0) | (E8, 56) if king => to + 2,
+        _ => to,
+    };
+
+    format!("{}{}{}", square_name(from), square_name(to), promotion)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_polyglot_key() {
+        // Reference keys from the Polyglot format description
+        let positions = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                0x463b96181691fc9c,
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                0x823c9b50fd114196,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                0x0756b94461c50fb0,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+                0x662fafb965db29d4,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                0x22a48b5a8e47ff78,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3",
+                0x652a607ca3f242c1,
+            ),
+            (
+                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+                0x00fdd303c946bdd9,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+                0x3c8123ea7b067637,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+                0x5c3f9b829b279560,
+            ),
+        ];
+        for (fen, key) in positions {
+            assert_eq!(polyglot_key(&Board::from_fen(fen).unwrap()), key, "{}", fen);
+        }
+    }
+
+    /// Encodes a move the way Polyglot stores it
+    fn encode(from: usize, to: usize) -> u16 {
+        ((from << 6) | to) as u16
+    }
+
+    fn write_book(name: &str, entries: &mut [(u64, u16, u16)]) -> std::path::PathBuf {
+        entries.sort_by_key(|entry| entry.0);
+        let path = std::env::temp_dir().join(name);
+        let mut file = File::create(&path).unwrap();
+        for (key, mv, weight) in entries.iter() {
+            file.write_all(&key.to_be_bytes()).unwrap();
+            file.write_all(&mv.to_be_bytes()).unwrap();
+            file.write_all(&weight.to_be_bytes()).unwrap();
+            file.write_all(&[0; 4]).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_probe() {
+        let start = polyglot_key(&Board::default());
+        let castling = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let path = write_book(
+            "rust_chess_test_probe.bin",
+            &mut [
+                (start, encode(12, 28), 10),
+                (start, encode(11, 27), 30),
+                (start - 1, encode(6, 21), 50),
+                (start + 1, encode(1, 18), 50),
+                // Illegal moves are skipped
+                (start, encode(12, 36), 99),
+                (polyglot_key(&castling), encode(E1, 7), 1),
+            ],
+        );
+
+        let mut book = Book::open(&path).unwrap();
+        let moves: Vec<(String, u16)> = book
+            .probe(&Board::default())
+            .unwrap()
+            .iter()
+            .map(|book_move| (book_move.mv.to_string(), book_move.weight))
+            .collect();
+        assert_eq!(moves, [("d2d4".to_string(), 30), ("e2e4".to_string(), 10)]);
+
+        let mv = book.pick(&castling).unwrap().unwrap();
+        assert_eq!(mv.to_string(), "e1g1");
+        assert!(mv.is_castle());
+
+        let empty = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(book.pick(&empty).unwrap(), None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use pieces::board;
+
+pub mod ai;
+pub mod book;
+pub mod pieces;
+pub mod uci;
+pub mod zobrist;
+
+pub struct Game {}
+
+/// Contains castling_rights, move_clocks, en_passant_square if possible and the side to move
+///
+/// Example
+/// ```
+/// use rust_chess::pieces::board::{Board, Side};
+///
+/// let board = Board::default();
+/// assert_eq!(board.state().stm(), Side::White);
+/// assert_eq!(board.state().full_move_counter(), 1);
+/// ```
+///
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct State {
+    castling_rights: CastlingRights,
+    en_passant_square: Option<board::Square>,
+    half_move_counter: u8,
+    full_move_counter: u16,
+    stm: board::Side,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            castling_rights: CastlingRights::all(),
+            en_passant_square: None,
+            half_move_counter: 0,
+            full_move_counter: 1,
+            stm: board::Side::White,
+        }
+    }
+}
+
+impl State {
+    /// Returns the castling rights
+    pub fn castling_rights(&self) -> &CastlingRights {
+        &self.castling_rights
+    }
+
+    /// Returns the en passant target square, if any
+    pub fn en_passant_square(&self) -> Option<board::Square> {
+        self.en_passant_square
+    }
+
+    /// Returns the number of half moves since the last capture or pawn move
+    pub fn half_move_counter(&self) -> u8 {
+        self.half_move_counter
+    }
+
+    /// Returns the full move number, starting at 1 and incremented after black moves
+    pub fn full_move_counter(&self) -> u16 {
+        self.full_move_counter
+    }
+
+    /// Returns the side to move
+    pub fn stm(&self) -> board::Side {
+        self.stm
+    }
+}
+
+/// Castling rights are stored in a ['u8'], which is divided into the following parts:
+/// ```text
+/// 0 1 0 1   0                 1                0                  1
+/// ^^^^^^^   ^                 ^                ^                  ^
+/// unused    Black queen side  Black king side  White queen side   White king side
+/// ```
+///
+/// Example
+/// ```
+/// use rust_chess::{Castling, CastlingRights};
+///
+/// let rights = CastlingRights::default();
+/// assert!(rights.contains(Castling::WHITE_00 | Castling::BLACK_000));
+/// ```
+///
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct CastlingRights(u8);
+
+impl CastlingRights {
+    pub fn empty() -> Self {
+        Self(Castling::NO_CASTLING)
+    }
+
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Returns the raw castling bits
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns true if every right in `rights` is available
+    pub fn contains(&self, rights: u8) -> bool {
+        self.0 & rights == rights
+    }
+
+    /// Grants the given rights
+    pub fn insert(&mut self, rights: u8) {
+        self.0 |= rights;
+    }
+
+    /// Revokes the given rights
+    pub fn remove(&mut self, rights: u8) {
+        self.0 &= !rights;
+    }
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        Self(Castling::ANY_CASTLING)
+    }
+}
+
+/// Provides labels for the ['CastlingRights']
+///
+/// Example
+/// ```
+/// use rust_chess::Castling;
+///
+/// assert_eq!(Castling::WHITE_00 | Castling::WHITE_000, Castling::WHITE_CASTLING);
+/// ```
+///
+pub struct Castling;
+
+impl Castling {
+    pub const NO_CASTLING: u8 = 0;
+    pub const WHITE_00: u8 = 0b00000001;
+    pub const WHITE_000: u8 = 0b00000010;
+    pub const BLACK_00: u8 = 0b00000100;
+    pub const BLACK_000: u8 = 0b00001000;
+
+    pub const KING_SIDE: u8 = Self::BLACK_00 | Self::WHITE_00;
+    pub const QUEEN_SIDE: u8 = Self::BLACK_000 | Self::WHITE_000;
+    pub const WHITE_CASTLING: u8 = Self::WHITE_00 | Self::WHITE_000;
+    pub const BLACK_CASTLING: u8 = Self::BLACK_00 | Self::BLACK_000;
+    pub const ANY_CASTLING: u8 = Self::WHITE_CASTLING | Self::BLACK_CASTLING;
+}
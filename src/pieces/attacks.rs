@@ -0,0 +1,231 @@
+use super::board::{
+    east_one, north_one, northeast_one, northwest_one, south_one, southeast_one, southwest_one,
+    west_one, Side, Square, POSITION_ARRAY, RAY_EAST, RAY_NORTH, RAY_NORTH_EAST, RAY_NORTH_WEST,
+    RAY_SOUTH, RAY_SOUTH_EAST, RAY_SOUTH_WEST, RAY_WEST,
+};
+use super::magic;
+
+/// Squares a knight on each square attacks
+pub const KNIGHT_ATTACKS: [u64; 64] = {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let bboard = POSITION_ARRAY[square];
+        let one = east_one(bboard) | west_one(bboard);
+        let two = east_one(east_one(bboard)) | west_one(west_one(bboard));
+        table[square] =
+            north_one(north_one(one)) | south_one(south_one(one)) | north_one(two) | south_one(two);
+        square += 1;
+    }
+    table
+};
+
+/// Squares a king on each square attacks
+pub const KING_ATTACKS: [u64; 64] = {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let bboard = POSITION_ARRAY[square];
+        let row = bboard | east_one(bboard) | west_one(bboard);
+        table[square] = (row | north_one(row) | south_one(row)) & !bboard;
+        square += 1;
+    }
+    table
+};
+
+/// Squares a pawn of each side on each square attacks
+pub const PAWN_ATTACKS: [[u64; 64]; 2] = {
+    let mut table = [[0; 64]; 2];
+    let mut square = 0;
+    while square < 64 {
+        let bboard = POSITION_ARRAY[square];
+        table[Side::White as usize][square] = northwest_one(bboard) | northeast_one(bboard);
+        table[Side::Black as usize][square] = southwest_one(bboard) | southeast_one(bboard);
+        square += 1;
+    }
+    table
+};
+
+/// File and rank steps of the eight ray directions
+const DIRECTIONS: [(i32, i32); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// Squares strictly between two squares on a shared rank, file or diagonal, empty otherwise
+pub static BETWEEN: [[u64; 64]; 64] = {
+    let mut table = [[0; 64]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut d = 0;
+        while d < 8 {
+            let (df, dr) = DIRECTIONS[d];
+            let mut file = (from % 8) as i32 + df;
+            let mut rank = (from / 8) as i32 + dr;
+            let mut between = 0;
+            while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                let to = (rank * 8 + file) as usize;
+                table[from][to] = between;
+                between |= POSITION_ARRAY[to];
+                file += df;
+                rank += dr;
+            }
+            d += 1;
+        }
+        from += 1;
+    }
+    table
+};
+
+/// The full rank, file or diagonal through two aligned squares, empty otherwise
+pub static LINE: [[u64; 64]; 64] = {
+    let mut table = [[0; 64]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut d = 0;
+        while d < 8 {
+            let (df, dr) = DIRECTIONS[d];
+            // Collect the whole line through `from` in both directions
+            let mut line = POSITION_ARRAY[from];
+            let mut step = -1;
+            while step <= 1 {
+                let mut file = (from % 8) as i32 + df * step;
+                let mut rank = (from / 8) as i32 + dr * step;
+                while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                    line |= POSITION_ARRAY[(rank * 8 + file) as usize];
+                    file += df * step;
+                    rank += dr * step;
+                }
+                step += 2;
+            }
+
+            let mut file = (from % 8) as i32 + df;
+            let mut rank = (from / 8) as i32 + dr;
+            while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[from][(rank * 8 + file) as usize] = line;
+                file += df;
+                rank += dr;
+            }
+            d += 1;
+        }
+        from += 1;
+    }
+    table
+};
+
+/// Returns the squares a knight on `square` attacks
+pub fn knight_attacks(square: Square) -> u64 {
+    KNIGHT_ATTACKS[square]
+}
+
+/// Returns the squares a king on `square` attacks
+pub fn king_attacks(square: Square) -> u64 {
+    KING_ATTACKS[square]
+}
+
+/// Returns the squares a pawn of `side` on `square` attacks
+pub fn pawn_attacks(side: Side, square: Square) -> u64 {
+    PAWN_ATTACKS[side.index()][square]
+}
+
+/// Returns the ray from `square` towards higher indices, stopping at the first blocker
+fn positive_ray(rays: &[u64; 64], square: Square, occupancy: u64) -> u64 {
+    let ray = rays[square] & !POSITION_ARRAY[square];
+    let blockers = ray & occupancy;
+    if blockers == 0 {
+        return ray;
+    }
+    let first = blockers.trailing_zeros() as usize;
+    ray & !(rays[first] & !POSITION_ARRAY[first])
+}
+
+/// Returns the ray from `square` towards lower indices, stopping at the first blocker
+fn negative_ray(rays: &[u64; 64], square: Square, occupancy: u64) -> u64 {
+    let ray = rays[square] & !POSITION_ARRAY[square];
+    let blockers = ray & occupancy;
+    if blockers == 0 {
+        return ray;
+    }
+    let first = 63 - blockers.leading_zeros() as usize;
+    ray & !(rays[first] & !POSITION_ARRAY[first])
+}
+
+/// Returns the squares a rook on `square` attacks, walking the `RAY_*` tables
+pub fn ray_rook_attacks(square: Square, occupancy: u64) -> u64 {
+    positive_ray(&RAY_NORTH, square, occupancy)
+        | positive_ray(&RAY_EAST, square, occupancy)
+        | negative_ray(&RAY_SOUTH, square, occupancy)
+        | negative_ray(&RAY_WEST, square, occupancy)
+}
+
+/// Returns the squares a bishop on `square` attacks, walking the `RAY_*` tables
+pub fn ray_bishop_attacks(square: Square, occupancy: u64) -> u64 {
+    positive_ray(&RAY_NORTH_EAST, square, occupancy)
+        | positive_ray(&RAY_NORTH_WEST, square, occupancy)
+        | negative_ray(&RAY_SOUTH_EAST, square, occupancy)
+        | negative_ray(&RAY_SOUTH_WEST, square, occupancy)
+}
+
+/// Returns the squares a rook on `square` attacks, looked up in the magic tables
+pub fn rook_attacks(square: Square, occupancy: u64) -> u64 {
+    magic::rook_attacks(square, occupancy)
+}
+
+/// Returns the squares a bishop on `square` attacks, looked up in the magic tables
+pub fn bishop_attacks(square: Square, occupancy: u64) -> u64 {
+    magic::bishop_attacks(square, occupancy)
+}
+
+/// Returns the squares a queen on `square` attacks
+pub fn queen_attacks(square: Square, occupancy: u64) -> u64 {
+    rook_attacks(square, occupancy) | bishop_attacks(square, occupancy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pieces::board::{A1, B3, C2, D4, E4, E5, H1, H8};
+
+    #[test]
+    fn test_leaper_attacks() {
+        assert_eq!(knight_attacks(A1).count_ones(), 2);
+        assert_eq!(knight_attacks(D4).count_ones(), 8);
+        assert_ne!(knight_attacks(A1) & POSITION_ARRAY[B3], 0);
+        assert_ne!(knight_attacks(A1) & POSITION_ARRAY[C2], 0);
+        assert_eq!(king_attacks(A1).count_ones(), 3);
+        assert_eq!(king_attacks(D4).count_ones(), 8);
+        assert_eq!(pawn_attacks(Side::White, H1).count_ones(), 1);
+        assert_eq!(
+            pawn_attacks(Side::Black, E5),
+            POSITION_ARRAY[D4] | POSITION_ARRAY[E4 + 1]
+        );
+    }
+
+    #[test]
+    fn test_slider_attacks() {
+        assert_eq!(rook_attacks(A1, 0).count_ones(), 14);
+        assert_eq!(bishop_attacks(D4, 0).count_ones(), 13);
+        assert_eq!(queen_attacks(D4, 0).count_ones(), 27);
+        // A blocker on e4 stops the rook on d4 going east, but e4 itself is attacked
+        let attacks = rook_attacks(D4, POSITION_ARRAY[E4]);
+        assert_ne!(attacks & POSITION_ARRAY[E4], 0);
+        assert_eq!(attacks & POSITION_ARRAY[E4 + 1], 0);
+        assert_eq!(attacks, ray_rook_attacks(D4, POSITION_ARRAY[E4]));
+    }
+
+    #[test]
+    fn test_between_and_line() {
+        assert_eq!(BETWEEN[A1][H8].count_ones(), 6);
+        assert_eq!(BETWEEN[A1][B3], 0);
+        assert_eq!(BETWEEN[D4][E4], 0);
+        assert_eq!(LINE[A1][D4], LINE[H8][E5]);
+        assert_eq!(LINE[A1][B3], 0);
+        assert_eq!(LINE[A1][H1].count_ones(), 8);
+    }
+}
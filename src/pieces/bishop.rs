@@ -1,31 +1,36 @@
-use super::board::{self, bitscan, RAY_NORTH_EAST, RAY_NORTH_WEST, RAY_SOUTH_EAST, RAY_SOUTH_WEST};
+use super::attacks;
+use super::board;
 use super::traits::Piece;
-use std::{fmt, ops};
 
 /// Bishopset structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BishopSet {
-    bboards: Vec<Bishop>,
+    pub bboard: u64,
     side: board::Side,
 }
 
 impl Piece for BishopSet {
-    /// Returns all moves
+    /// Returns a bitboard of all valid moves in a BishopSet
     fn moves(&self) -> Vec<board::Move> {
         let mut moves: Vec<board::Move> = Vec::new();
-
-        for bishop in self.bboards.iter() {
+        let mut set = self.bboard;
+        while set != 0 {
+            let bishop = Bishop::new(set & set.wrapping_neg(), self.side);
             moves.extend(bishop.moves());
+            set &= set - 1;
         }
 
         moves
     }
 
-    /// Returns all attacks
+    /// Returns a bitboard of all valid attacks in a BishopSet
     fn attacks(&self, blockers: u64) -> Vec<board::Move> {
         let mut moves: Vec<board::Move> = Vec::new();
-
-        for bishop in self.bboards {
+        let mut set = self.bboard;
+        while set != 0 {
+            let bishop = Bishop::new(set & set.wrapping_neg(), self.side);
             moves.extend(bishop.attacks(blockers));
+            set &= set - 1;
         }
 
         moves
@@ -37,39 +42,29 @@ impl Piece for BishopSet {
 
     /// Returns the board
     fn board(&self) -> u64 {
-        let mut board: u64 = 0;
-        for bishop in self.bboards {
-            board &= bishop.board();
-        }
-        board
+        self.bboard
     }
 }
 
 impl BishopSet {
     /// Creates a new BishopSet of the given side
     pub fn new(side: board::Side) -> BishopSet {
-        let mut bboards: Vec<Bishop> = Vec::new();
-
-        match side {
-            board::Side::White => {
-                bboards.push(Bishop::new(board::POSITION_ARRAY[board::C1], side));
-                bboards.push(Bishop::new(board::POSITION_ARRAY[board::F1], side));
-            }
-            board::Side::Black => {
-                bboards.push(Bishop::new(board::POSITION_ARRAY[board::C8], side));
-                bboards.push(Bishop::new(board::POSITION_ARRAY[board::F8], side));
-            }
-        }
+        let bboard = match side {
+            board::Side::White => Bishop::WHITE_DEFAULT,
+            board::Side::Black => Bishop::BLACK_DEFAULT,
+        };
 
-        BishopSet { bboards, side }
+        BishopSet { bboard, side }
     }
 
     /// Evaluate the BishopSet
     pub fn evaluate(&self) -> i32 {
         let mut score = 0;
-
-        for bishop in self.bboards {
+        let mut set = self.bboard;
+        while set != 0 {
+            let bishop = Bishop::new(set & set.wrapping_neg(), self.side);
             score += 3 * bishop.piece_square_value();
+            set &= set - 1;
         }
 
         score
@@ -77,6 +72,7 @@ impl BishopSet {
 }
 
 /// Bishop structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Bishop {
     bboard: u64,
     side: board::Side,
@@ -86,7 +82,7 @@ impl Bishop {
     /// Default white bishop board
     pub const WHITE_DEFAULT: u64 = 0x24;
     /// Default black bishop board
-    pub const BLACK_DEFAULT: u64 = 2400000000000000;
+    pub const BLACK_DEFAULT: u64 = 0x2400000000000000;
     pub const BISHOP_TABLE: [i32; 64] = [
         -20, -10, -10, -10, -10, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 10, 10, 5,
         0, -10, -10, 5, 5, 10, 10, 5, 5, -10, -10, 0, 10, 10, 10, 10, 0, -10, -10, 10, 10, 10, 10,
@@ -95,47 +91,30 @@ impl Bishop {
 
     /// Return's a new Bishop bboard
     pub fn new(pos: u64, side: board::Side) -> Self {
-        todo!()
+        Bishop { bboard: pos, side }
     }
 }
 
 impl Piece for Bishop {
-    /// Returns a bitboard of valid moves
+    /// Returns all moves of the Bishop on an empty board
     fn moves(&self) -> Vec<board::Move> {
-        let mut moves: Vec<board::Move> = Vec::new();
-
-        let index = board::bitscan(self.bboard).unwrap() as usize;
-        let mut set = RAY_NORTH_EAST[index]
-            | RAY_NORTH_WEST[index]
-            | RAY_SOUTH_EAST[index]
-            | RAY_SOUTH_WEST[index];
-
-        while set != 0 {
-            let to = set.trailing_zeros();
-            moves.push(board::Move {
-                from: self.bboard.trailing_zeros() as usize,
-                to: to as usize,
-                flags: board::BoardFlags::empty(),
-            });
-            set &= set - 1;
-        }
-
-        moves
+        self.attacks(0)
     }
 
-    /// Returns a bitboard of valid attacks
+    /// Returns all attacks of the Bishop, stopping at the first blocker on each ray
     fn attacks(&self, blockers: u64) -> Vec<board::Move> {
-        self.moves()
+        let from = self.bboard.trailing_zeros() as usize;
+        board::serialize_moves(
+            from,
+            attacks::bishop_attacks(from, blockers),
+            board::BoardFlags::bishop | self.side.flag(),
+        )
     }
 
     fn piece_square_value(&self) -> i32 {
         match self.side {
-            board::Side::White => {
-                return Bishop::BISHOP_TABLE[self.bboard.trailing_zeros() as usize]
-            }
-            board::Side::Black => {
-                return Bishop::BISHOP_TABLE[63 - self.bboard.trailing_zeros() as usize]
-            }
+            board::Side::White => Bishop::BISHOP_TABLE[self.bboard.trailing_zeros() as usize],
+            board::Side::Black => Bishop::BISHOP_TABLE[63 - self.bboard.trailing_zeros() as usize],
         }
     }
 
@@ -150,5 +129,10 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_bishop() {}
+    fn test_bishop() {
+        let bishop = Bishop::new(board::POSITION_ARRAY[board::D4], board::Side::Black);
+        assert_eq!(bishop.moves().len(), 13);
+        assert!(bishop.moves().iter().all(|mv| mv.to != board::D4));
+        assert_eq!(bishop.attacks(board::POSITION_ARRAY[board::E5]).len(), 10);
+    }
 }
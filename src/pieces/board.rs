@@ -2,8 +2,10 @@ use super::{
     bishop::BishopSet, king::King, knight::KnightSet, pawn::PawnSet, queen::Queen, rook::RookSet,
     traits::Piece,
 };
+use crate::zobrist::KEYS;
+use crate::{Castling, State};
 use bitflags::bitflags;
-use std::collections::HashMap;
+use std::fmt;
 
 bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -20,63 +22,526 @@ bitflags! {
         const white         = 0b001000000000;
         const black         = 0b010000000000;
         const promotion     = 0b100000000000;
+        const king          = 0b1000000000000;
+    }
+}
+
+/// Index of a square, from A1 = 0 to H8 = 63
+pub type Square = usize;
+
+/// The kinds of chess pieces
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum PieceType {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl PieceType {
+    /// Every piece type, in index order
+    pub const ALL: [PieceType; 6] = [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+        PieceType::King,
+    ];
+
+    /// Returns the index of the piece type, from pawn = 0 to king = 5
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the material value of the piece type in centipawns, 0 for the king
+    pub const fn value(self) -> i32 {
+        match self {
+            PieceType::Pawn => 100,
+            PieceType::Knight => 320,
+            PieceType::Bishop => 330,
+            PieceType::Rook => 500,
+            PieceType::Queen => 900,
+            PieceType::King => 0,
+        }
+    }
+
+    /// Returns the [`BoardFlags`] label of the piece type
+    pub fn flag(self) -> BoardFlags {
+        match self {
+            PieceType::Pawn => BoardFlags::pawn,
+            PieceType::Knight => BoardFlags::knight,
+            PieceType::Bishop => BoardFlags::bishop,
+            PieceType::Rook => BoardFlags::rook,
+            PieceType::Queen => BoardFlags::queen,
+            PieceType::King => BoardFlags::king,
+        }
+    }
+
+    /// Parses a FEN piece letter, upper case for white and lower case for black
+    pub fn from_char(c: char) -> Option<(Side, PieceType)> {
+        let side = if c.is_ascii_uppercase() {
+            Side::White
+        } else {
+            Side::Black
+        };
+        let piece = match c.to_ascii_lowercase() {
+            'p' => PieceType::Pawn,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'r' => PieceType::Rook,
+            'q' => PieceType::Queen,
+            'k' => PieceType::King,
+            _ => return None,
+        };
+        Some((side, piece))
+    }
+
+    /// Returns the FEN piece letter for the given side
+    pub fn to_char(self, side: Side) -> char {
+        let c = match self {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k',
+        };
+        match side {
+            Side::White => c.to_ascii_uppercase(),
+            Side::Black => c,
+        }
     }
 }
 
 /// Represents a move
+///
+/// The flags carry the color and kind of the moving piece. On a promotion the piece flag
+/// names the piece the pawn promotes to. En passant captures carry both `capture` and
+/// `passant`, and castling moves are king moves from its home square with `castle_king` or
+/// `castle_queen` set.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Move {
     pub from: usize,
     pub to: usize,
     pub flags: BoardFlags,
 }
 
+impl Move {
+    /// A placeholder move from a1 to a1 that never occurs in play
+    pub const NULL: Move = Move {
+        from: A1,
+        to: A1,
+        flags: BoardFlags::empty(),
+    };
+
+    /// Creates a new move
+    pub fn new(from: Square, to: Square, flags: BoardFlags) -> Self {
+        Move { from, to, flags }
+    }
+
+    /// Returns the side making the move
+    pub fn side(&self) -> Side {
+        if self.flags.contains(BoardFlags::black) {
+            Side::Black
+        } else {
+            Side::White
+        }
+    }
+
+    /// Returns the kind of piece that moves
+    pub fn piece(&self) -> PieceType {
+        if self.flags.contains(BoardFlags::promotion) {
+            return PieceType::Pawn;
+        }
+        self.flag_piece()
+    }
+
+    /// Returns the piece a pawn promotes to
+    pub fn promotion(&self) -> Option<PieceType> {
+        if self.flags.contains(BoardFlags::promotion) {
+            Some(self.flag_piece())
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the move captures a piece
+    pub fn is_capture(&self) -> bool {
+        self.flags.contains(BoardFlags::capture)
+    }
+
+    /// Returns true if the move is an en passant capture
+    pub fn is_en_passant(&self) -> bool {
+        self.flags.contains(BoardFlags::passant)
+    }
+
+    /// Returns true if the move castles either way
+    pub fn is_castle(&self) -> bool {
+        self.flags
+            .intersects(BoardFlags::castle_king | BoardFlags::castle_queen)
+    }
+
+    /// Returns true if the move neither captures nor promotes
+    pub fn is_quiet(&self) -> bool {
+        !self
+            .flags
+            .intersects(BoardFlags::capture | BoardFlags::promotion)
+    }
+
+    /// Returns the piece named by the piece flags
+    fn flag_piece(&self) -> PieceType {
+        PieceType::ALL
+            .into_iter()
+            .find(|piece| self.flags.contains(piece.flag()))
+            .unwrap_or(PieceType::King)
+    }
+}
+
+/// Formats the move in UCI long algebraic notation, e.g. `e2e4` or `e7e8q`
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            super::fen::square_name(self.from),
+            super::fen::square_name(self.to)
+        )?;
+        if let Some(piece) = self.promotion() {
+            write!(f, "{}", piece.to_char(Side::Black))?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything [`Board::unmake_move`] needs to restore the position before a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    /// The piece taken by the move, if any
+    pub captured: Option<PieceType>,
+    /// The state before the move
+    pub state: State,
+    /// The Zobrist hash before the move
+    pub hash: u64,
+}
+
+/// Castling rights that survive a move touching each square
+const CASTLING_MASK: [u8; 64] = {
+    let mut mask = [Castling::ANY_CASTLING; 64];
+    mask[A1] = !Castling::WHITE_000;
+    mask[E1] = !Castling::WHITE_CASTLING;
+    mask[H1] = !Castling::WHITE_00;
+    mask[A8] = !Castling::BLACK_000;
+    mask[E8] = !Castling::BLACK_CASTLING;
+    mask[H8] = !Castling::BLACK_00;
+    mask
+};
+
+/// Returns the rook's from and to squares for a castling move of the king to `to`
+fn castling_rook(to: Square) -> (Square, Square) {
+    match to {
+        G1 => (H1, F1),
+        C1 => (A1, D1),
+        G8 => (H8, F8),
+        C8 => (A8, D8),
+        _ => unreachable!("castling king cannot land on {}", to),
+    }
+}
+
 /// Represents a board
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Board {
     white: Player,
     black: Player,
+    state: State,
+    /// Zobrist hash of the position, kept up to date by every change to the board
+    hash: u64,
 }
 
 /// Default board
 impl Default for Board {
     fn default() -> Self {
-        Board {
+        let mut board = Board {
             white: Player::new(Side::White),
             black: Player::new(Side::Black),
-        }
+            state: State::default(),
+            hash: 0,
+        };
+        board.hash = board.compute_hash();
+        board
     }
 }
 
 impl Board {
-    /// Check if the game is over
-    pub fn is_game_over(&self) -> bool {
-        if self.white.moves().len() == 0 || self.black.moves().len() == 0 {
-            return true;
+    /// Creates a board with no pieces on it and a default [`State`]
+    pub fn empty() -> Self {
+        let mut board = Board {
+            white: Player::empty(Side::White),
+            black: Player::empty(Side::Black),
+            state: State::default(),
+            hash: 0,
+        };
+        board.hash = board.compute_hash();
+        board
+    }
+
+    /// Returns the Zobrist hash of the position
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::pieces::board::Board;
+    ///
+    /// let mut board = Board::default();
+    /// let hash = board.hash();
+    /// let mv = board.legal_moves()[0];
+    /// let undo = board.make_move(mv);
+    /// assert_ne!(board.hash(), hash);
+    /// board.unmake_move(mv, undo);
+    /// assert_eq!(board.hash(), hash);
+    /// ```
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Computes the Zobrist hash of the position from scratch
+    pub fn compute_hash(&self) -> u64 {
+        KEYS.hash(self)
+    }
+
+    /// Recomputes the hash after the [`State`] was changed through [`Board::state_mut`]
+    pub(crate) fn refresh_hash(&mut self) {
+        self.hash = self.compute_hash();
+    }
+
+    /// Returns the castling rights, clocks, en passant square and side to move
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Returns the mutable [`State`]
+    pub(crate) fn state_mut(&mut self) -> &mut State {
+        &mut self.state
+    }
+
+    /// Returns the pieces of the given side
+    pub fn player(&self, side: Side) -> &Player {
+        match side {
+            Side::White => &self.white,
+            Side::Black => &self.black,
+        }
+    }
+
+    /// Returns the mutable pieces of the given side
+    pub(crate) fn player_mut(&mut self, side: Side) -> &mut Player {
+        match side {
+            Side::White => &mut self.white,
+            Side::Black => &mut self.black,
+        }
+    }
+
+    /// Returns a bitboard of every piece of the given kind and side
+    pub fn pieces(&self, side: Side, piece: PieceType) -> u64 {
+        self.player(side).pieces(piece)
+    }
+
+    /// Returns a bitboard of every occupied square
+    pub fn occupancy(&self) -> u64 {
+        self.white.board() | self.black.board()
+    }
+
+    /// Returns the side and kind of the piece on a square
+    pub fn piece_on(&self, square: Square) -> Option<(Side, PieceType)> {
+        let bit = POSITION_ARRAY[square];
+        for side in [Side::White, Side::Black] {
+            for piece in PieceType::ALL {
+                if self.pieces(side, piece) & bit != 0 {
+                    return Some((side, piece));
+                }
+            }
+        }
+        None
+    }
+
+    /// Puts a piece on an empty square
+    pub(crate) fn put_piece(&mut self, side: Side, piece: PieceType, square: Square) {
+        *self.player_mut(side).pieces_mut(piece) |= POSITION_ARRAY[square];
+        self.hash ^= KEYS.piece(side, piece, square);
+    }
+
+    /// Removes a piece from a square
+    pub(crate) fn remove_piece(&mut self, side: Side, piece: PieceType, square: Square) {
+        *self.player_mut(side).pieces_mut(piece) &= !POSITION_ARRAY[square];
+        self.hash ^= KEYS.piece(side, piece, square);
+    }
+
+    /// Moves a piece between two squares
+    fn shift_piece(&mut self, side: Side, piece: PieceType, from: Square, to: Square) {
+        *self.player_mut(side).pieces_mut(piece) ^= POSITION_ARRAY[from] | POSITION_ARRAY[to];
+        self.hash ^= KEYS.piece(side, piece, from) ^ KEYS.piece(side, piece, to);
+    }
+
+    /// Plays a move and returns what is needed to take it back
+    ///
+    /// The move must be pseudo-legal in the current position, with flags set the way the move
+    /// generator sets them.
+    pub fn make_move(&mut self, mv: Move) -> Undo {
+        let us = self.state.stm;
+        let them = us.opposite();
+        let piece = mv.piece();
+        let mut undo = Undo {
+            captured: None,
+            state: self.state,
+            hash: self.hash,
+        };
+        debug_assert_eq!(mv.side(), us, "{} is not a move for {:?}", mv, us);
+        debug_assert_eq!(self.piece_on(mv.from), Some((us, piece)), "{}", mv);
+
+        if mv.is_en_passant() {
+            let square = match us {
+                Side::White => mv.to - 8,
+                Side::Black => mv.to + 8,
+            };
+            self.remove_piece(them, PieceType::Pawn, square);
+            undo.captured = Some(PieceType::Pawn);
+        } else if mv.is_capture() {
+            let captured = PieceType::ALL
+                .into_iter()
+                .find(|captured| self.pieces(them, *captured) & POSITION_ARRAY[mv.to] != 0)
+                .expect("capture without a piece on the target square");
+            self.remove_piece(them, captured, mv.to);
+            undo.captured = Some(captured);
+        }
+
+        match mv.promotion() {
+            Some(promotion) => {
+                self.remove_piece(us, PieceType::Pawn, mv.from);
+                self.put_piece(us, promotion, mv.to);
+            }
+            None => self.shift_piece(us, piece, mv.from, mv.to),
+        }
+
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook(mv.to);
+            self.shift_piece(us, PieceType::Rook, rook_from, rook_to);
+        }
+
+        let state = &mut self.state;
+        self.hash ^=
+            KEYS.castling(state.castling_rights.0) ^ KEYS.en_passant(state.en_passant_square);
+        state.castling_rights.0 &= CASTLING_MASK[mv.from] & CASTLING_MASK[mv.to];
+        state.en_passant_square = None;
+        if piece == PieceType::Pawn && mv.from.abs_diff(mv.to) == 16 {
+            state.en_passant_square = Some((mv.from + mv.to) / 2);
+        }
+        if piece == PieceType::Pawn || undo.captured.is_some() {
+            state.half_move_counter = 0;
+        } else {
+            state.half_move_counter = state.half_move_counter.saturating_add(1);
+        }
+        if us == Side::Black {
+            state.full_move_counter = state.full_move_counter.saturating_add(1);
+        }
+        state.stm = them;
+        self.hash ^= KEYS.castling(state.castling_rights.0)
+            ^ KEYS.en_passant(state.en_passant_square)
+            ^ KEYS.side();
+        debug_assert_eq!(self.hash, self.compute_hash(), "hash after {}", mv);
+
+        undo
+    }
+
+    /// Takes back a move played by [`Board::make_move`]
+    pub fn unmake_move(&mut self, mv: Move, undo: Undo) {
+        self.state = undo.state;
+        let us = self.state.stm;
+        let them = us.opposite();
+
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook(mv.to);
+            self.shift_piece(us, PieceType::Rook, rook_to, rook_from);
+        }
+
+        match mv.promotion() {
+            Some(promotion) => {
+                self.remove_piece(us, promotion, mv.to);
+                self.put_piece(us, PieceType::Pawn, mv.from);
+            }
+            None => self.shift_piece(us, mv.piece(), mv.to, mv.from),
         }
 
-        false
+        if let Some(captured) = undo.captured {
+            let square = match (mv.is_en_passant(), us) {
+                (false, _) => mv.to,
+                (true, Side::White) => mv.to - 8,
+                (true, Side::Black) => mv.to + 8,
+            };
+            self.put_piece(them, captured, square);
+        }
+
+        self.hash = undo.hash;
+        debug_assert_eq!(self.hash, self.compute_hash(), "hash after undoing {}", mv);
+    }
+
+    /// Passes the turn to the other side without moving, for null move pruning
+    ///
+    /// The side to move must not be in check.
+    pub fn make_null_move(&mut self) -> Undo {
+        let undo = Undo {
+            captured: None,
+            state: self.state,
+            hash: self.hash,
+        };
+        let state = &mut self.state;
+        self.hash ^= KEYS.en_passant(state.en_passant_square) ^ KEYS.side();
+        state.en_passant_square = None;
+        state.stm = state.stm.opposite();
+        undo
+    }
+
+    /// Takes back a move played by [`Board::make_null_move`]
+    pub fn unmake_null_move(&mut self, undo: Undo) {
+        self.state = undo.state;
+        self.hash = undo.hash;
+    }
+
+    /// Returns true if `side` has a piece other than pawns and its king, without which null
+    /// move pruning is unsafe because zugzwang is likely
+    pub fn has_non_pawn_material(&self, side: Side) -> bool {
+        let player = self.player(side);
+        player.board() != player.pieces(PieceType::Pawn) | player.pieces(PieceType::King)
+    }
+
+    /// Check if the game is over, either because the side to move has no legal moves or
+    /// because of the fifty-move rule
+    pub fn is_game_over(&self) -> bool {
+        self.state.half_move_counter >= 100 || self.legal_moves().is_empty()
     }
 
-    /// Evaluate the game board
+    /// Evaluate the game board from the side to move's point of view
     pub fn evaluate(&self) -> i32 {
-        let mut score = 0;
-        0
+        let score = self.white.evaluate() - self.black.evaluate();
+        match self.state.stm {
+            Side::White => score,
+            Side::Black => -score,
+        }
     }
 
+    /// Returns every legal move for the side to move
     pub fn moves(&self) -> Vec<Move> {
-        todo!()
+        self.legal_moves()
     }
 
     /// Count pieces
     /// side: white or black
-    pub fn count_pieces(&self, side: Side) {
-        match side {
-            Side::White => (),
-            Side::Black => (),
-        }
+    pub fn count_pieces(&self, side: Side) -> u32 {
+        self.player(side).board().count_ones()
     }
 }
 
 /// Player structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Player {
     pawns: PawnSet,
     knights: KnightSet,
@@ -132,12 +597,12 @@ impl Piece for Player {
         let mut board: u64 = 0;
 
         // add the pieces
-        board &= self.pawns.board();
-        board &= self.knights.board();
-        board &= self.bishops.board();
-        board &= self.rooks.board();
-        board &= self.queen.board();
-        board &= self.king.board();
+        board |= self.pawns.board();
+        board |= self.knights.board();
+        board |= self.bishops.board();
+        board |= self.rooks.board();
+        board |= self.queen.board();
+        board |= self.king.board();
 
         // return the board
         board
@@ -157,6 +622,39 @@ impl Player {
         }
     }
 
+    /// Creates a player without any pieces
+    pub fn empty(side: Side) -> Self {
+        let mut player = Player::new(side);
+        for piece in PieceType::ALL {
+            *player.pieces_mut(piece) = EMPTY;
+        }
+        player
+    }
+
+    /// Returns a bitboard of every piece of the given kind
+    pub fn pieces(&self, piece: PieceType) -> u64 {
+        match piece {
+            PieceType::Pawn => self.pawns.board(),
+            PieceType::Knight => self.knights.board(),
+            PieceType::Bishop => self.bishops.board(),
+            PieceType::Rook => self.rooks.board(),
+            PieceType::Queen => self.queen.board(),
+            PieceType::King => self.king.board(),
+        }
+    }
+
+    /// Returns the mutable bitboard of every piece of the given kind
+    pub(crate) fn pieces_mut(&mut self, piece: PieceType) -> &mut u64 {
+        match piece {
+            PieceType::Pawn => &mut self.pawns.bboard,
+            PieceType::Knight => &mut self.knights.bboard,
+            PieceType::Bishop => &mut self.bishops.bboard,
+            PieceType::Rook => &mut self.rooks.bboard,
+            PieceType::Queen => &mut self.queen.bboard,
+            PieceType::King => &mut self.king.bboard,
+        }
+    }
+
     /// Evaluate the board
     pub fn evaluate(&self) -> i32 {
         // create new score
@@ -167,9 +665,14 @@ impl Player {
         score += self.knights.evaluate();
         score += self.bishops.evaluate();
         score += self.rooks.evaluate();
-        score += 9 * self.queen.piece_square_value();
+        score += self.queen.evaluate();
         score += self.king.piece_square_value();
 
+        // add the material
+        for piece in PieceType::ALL {
+            score += self.pieces(piece).count_ones() as i32 * piece.value();
+        }
+
         // return score
         score
     }
@@ -857,12 +1360,35 @@ pub const RAY_SOUTH_EAST: [u64; 64] = [
     0x8000000000000000,
 ];
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Side {
     White,
     Black,
 }
 
+impl Side {
+    /// Returns the index of the side, white = 0 and black = 1
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the other side
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::White => Side::Black,
+            Side::Black => Side::White,
+        }
+    }
+
+    /// Returns the [`BoardFlags`] label of the side
+    pub fn flag(self) -> BoardFlags {
+        match self {
+            Side::White => BoardFlags::white,
+            Side::Black => BoardFlags::black,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum Ray {
     North,
@@ -922,6 +1448,19 @@ pub const LIGHT_SQUARES: u64 = 0x55AA55AA55AA55AA;
 /// Full dark squares
 pub const DARK_SQUARES: u64 = 0xAA55AA55AA55AA55;
 
+/// Turns a bitboard of target squares into moves from `from`, all sharing the same flags
+pub fn serialize_moves(from: Square, mut targets: u64, flags: BoardFlags) -> Vec<Move> {
+    let mut moves: Vec<Move> = Vec::new();
+
+    while targets != 0 {
+        let to = targets.trailing_zeros() as usize;
+        moves.push(Move::new(from, to, flags));
+        targets &= targets - 1;
+    }
+
+    moves
+}
+
 /// Find the first set bit
 pub fn bitscan(board: u64) -> Option<u64> {
     if board == 0 {
@@ -931,67 +1470,67 @@ pub fn bitscan(board: u64) -> Option<u64> {
     Some(board.trailing_zeros() as u64)
 }
 
-pub fn get_rows_and_col(board: u64) -> u64 {
+pub fn get_rows_and_col(_board: u64) -> u64 {
     todo!()
 }
 
-pub fn get_diagonals(board: u64) -> u64 {
+pub fn get_diagonals(_board: u64) -> u64 {
     todo!()
 }
 
 /// Shift board northwest one square
-pub fn northwest_one(board: u64) -> u64 {
+pub const fn northwest_one(board: u64) -> u64 {
     (board << 7) & !H_FILE
 }
 
 /// Shift board north one square
-pub fn north_one(board: u64) -> u64 {
+pub const fn north_one(board: u64) -> u64 {
     board << 8
 }
 
 /// Shift board northeast one square
-pub fn northeast_one(board: u64) -> u64 {
+pub const fn northeast_one(board: u64) -> u64 {
     (board << 9) & !A_FILE
 }
 
 /// Shift board west one square
-pub fn west_one(board: u64) -> u64 {
+pub const fn west_one(board: u64) -> u64 {
     (board >> 1) & !H_FILE
 }
 
 /// Shift board east one square
-pub fn east_one(board: u64) -> u64 {
+pub const fn east_one(board: u64) -> u64 {
     (board << 1) & !A_FILE
 }
 
 /// Shift board southwest one square
-pub fn southwest_one(board: u64) -> u64 {
+pub const fn southwest_one(board: u64) -> u64 {
     (board >> 9) & !H_FILE
 }
 
 /// Shift board south one square
-pub fn south_one(board: u64) -> u64 {
+pub const fn south_one(board: u64) -> u64 {
     board >> 8
 }
 
 /// Shift board southeast one square
-pub fn southeast_one(board: u64) -> u64 {
+pub const fn southeast_one(board: u64) -> u64 {
     (board >> 7) & !A_FILE
 }
 
 /// Rotate board left
 pub fn rotate_left(board: u64, s: i32) -> u64 {
-    (board << s) | board >> (64 - s)
+    board.rotate_left(s as u32)
 }
 
 /// Rotate board right
 pub fn rotate_right(board: u64, s: i32) -> u64 {
-    (board >> s) | board << (64 - s)
+    board.rotate_right(s as u32)
 }
 
 /// Get a ray from a board in a given direction
 pub fn get_ray(mut board: u64, direction: Ray) -> u64 {
-    let mut temp = board.clone();
+    let mut temp = board;
 
     match direction {
         Ray::North => {
@@ -1060,4 +1599,222 @@ pub fn get_ray(mut board: u64, direction: Ray) -> u64 {
 #[cfg(test)]
 mod test {
     use super::*;
+
+    #[test]
+    fn test_default_board() {
+        let board = Board::default();
+        assert_eq!(board.occupancy(), RANK_1 | RANK_2 | RANK_7 | RANK_8);
+        assert_eq!(board.piece_on(E1), Some((Side::White, PieceType::King)));
+        assert_eq!(board.piece_on(D8), Some((Side::Black, PieceType::Queen)));
+        assert_eq!(board.piece_on(E4), None);
+        assert_eq!(board.count_pieces(Side::Black), 16);
+    }
+
+    /// Plays a move, checks the resulting FEN, takes it back and checks the board is restored
+    fn check_make_unmake(fen: &str, mv: Move, expected: &str) {
+        let mut board = Board::from_fen(fen).unwrap();
+        let undo = board.make_move(mv);
+        assert_eq!(board.to_fen(), expected, "{}", mv);
+        board.unmake_move(mv, undo);
+        assert_eq!(board, Board::from_fen(fen).unwrap(), "{}", mv);
+    }
+
+    #[test]
+    fn test_make_unmake_quiet() {
+        let start = crate::pieces::fen::START_FEN;
+        check_make_unmake(
+            start,
+            Move::new(G1, F3, BoardFlags::white | BoardFlags::knight),
+            "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1",
+        );
+        check_make_unmake(
+            start,
+            Move::new(E2, E4, BoardFlags::white | BoardFlags::pawn),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        );
+        check_make_unmake(
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            Move::new(G8, F6, BoardFlags::black | BoardFlags::knight),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2",
+        );
+    }
+
+    #[test]
+    fn test_make_unmake_capture() {
+        check_make_unmake(
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+            Move::new(
+                E4,
+                D5,
+                BoardFlags::white | BoardFlags::pawn | BoardFlags::capture,
+            ),
+            "rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+        );
+        // Taking a rook on its home square removes the castling right
+        check_make_unmake(
+            "r3k2r/8/8/8/8/8/6b1/R3K2R b KQkq - 3 10",
+            Move::new(
+                G2,
+                H1,
+                BoardFlags::black | BoardFlags::bishop | BoardFlags::capture,
+            ),
+            "r3k2r/8/8/8/8/8/8/R3K2b w Qkq - 0 11",
+        );
+    }
+
+    #[test]
+    fn test_make_unmake_en_passant() {
+        check_make_unmake(
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            Move::new(
+                E5,
+                F6,
+                BoardFlags::white | BoardFlags::pawn | BoardFlags::capture | BoardFlags::passant,
+            ),
+            "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3",
+        );
+        check_make_unmake(
+            "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1",
+            Move::new(
+                D4,
+                E3,
+                BoardFlags::black | BoardFlags::pawn | BoardFlags::capture | BoardFlags::passant,
+            ),
+            "4k3/8/8/8/8/4p3/8/4K3 w - - 0 2",
+        );
+    }
+
+    #[test]
+    fn test_make_unmake_castling() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        check_make_unmake(
+            fen,
+            Move::new(
+                E1,
+                G1,
+                BoardFlags::white | BoardFlags::king | BoardFlags::castle_king,
+            ),
+            "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1",
+        );
+        check_make_unmake(
+            fen,
+            Move::new(
+                E1,
+                C1,
+                BoardFlags::white | BoardFlags::king | BoardFlags::castle_queen,
+            ),
+            "r3k2r/8/8/8/8/8/8/2KR3R b kq - 1 1",
+        );
+        check_make_unmake(
+            "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+            Move::new(
+                E8,
+                C8,
+                BoardFlags::black | BoardFlags::king | BoardFlags::castle_queen,
+            ),
+            "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 1 2",
+        );
+        check_make_unmake(
+            fen,
+            Move::new(
+                A1,
+                A8,
+                BoardFlags::white | BoardFlags::rook | BoardFlags::capture,
+            ),
+            "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1",
+        );
+    }
+
+    #[test]
+    fn test_make_unmake_promotion() {
+        check_make_unmake(
+            "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            Move::new(
+                A7,
+                A8,
+                BoardFlags::white | BoardFlags::queen | BoardFlags::promotion,
+            ),
+            "Qr2k3/8/8/8/8/8/8/4K3 b - - 0 1",
+        );
+        check_make_unmake(
+            "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            Move::new(
+                A7,
+                B8,
+                BoardFlags::white
+                    | BoardFlags::knight
+                    | BoardFlags::promotion
+                    | BoardFlags::capture,
+            ),
+            "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1",
+        );
+    }
+
+    /// Plays moves given in UCI notation
+    fn play(board: &mut Board, moves: &[&str]) {
+        for name in moves {
+            let mv = board
+                .legal_moves()
+                .into_iter()
+                .find(|mv| mv.to_string() == *name)
+                .unwrap();
+            board.make_move(mv);
+        }
+    }
+
+    #[test]
+    fn test_hash_transpositions() {
+        // The same position reached by different move orders hashes the same
+        let mut a = Board::default();
+        let mut b = Board::default();
+        play(&mut a, &["g1f3", "g8f6", "b1c3"]);
+        play(&mut b, &["b1c3", "g8f6", "g1f3"]);
+        assert_eq!(a.hash(), b.hash());
+        assert_eq!(a.hash(), Board::from_fen(&a.to_fen()).unwrap().hash());
+
+        // Clocks are not hashed, castling rights and en passant files are
+        play(&mut a, &["f6g8", "c3b1", "g8f6", "b1c3"]);
+        assert_eq!(a.hash(), b.hash());
+        let mut c = Board::default();
+        play(&mut c, &["e2e4"]);
+        let without_ep =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_ne!(c.hash(), without_ep.unwrap().hash());
+        play(&mut a, &["h8g8", "h1g1", "g8h8", "g1h1"]);
+        assert_ne!(a.hash(), b.hash());
+    }
+
+    #[test]
+    fn test_null_move() {
+        let mut board = Board::default();
+        play(&mut board, &["e2e4"]);
+        let before = board.clone();
+        let undo = board.make_null_move();
+        assert_eq!(board.state().stm(), Side::White);
+        assert_eq!(board.state().en_passant_square(), None);
+        assert_eq!(board.hash(), board.compute_hash());
+        board.unmake_null_move(undo);
+        assert_eq!(board, before);
+
+        assert!(board.has_non_pawn_material(Side::White));
+        let pawns = Board::from_fen("4k3/4p3/8/8/8/8/1N2P3/4K3 w - - 0 1").unwrap();
+        assert!(pawns.has_non_pawn_material(Side::White));
+        assert!(!pawns.has_non_pawn_material(Side::Black));
+    }
+
+    #[test]
+    fn test_move_display() {
+        let mv = Move::new(
+            E7,
+            E8,
+            BoardFlags::white | BoardFlags::rook | BoardFlags::promotion,
+        );
+        assert_eq!(mv.to_string(), "e7e8r");
+        assert_eq!(mv.piece(), PieceType::Pawn);
+        assert_eq!(mv.promotion(), Some(PieceType::Rook));
+        assert_eq!(
+            Move::new(E1, G1, BoardFlags::white | BoardFlags::king).to_string(),
+            "e1g1"
+        );
+    }
 }
@@ -0,0 +1,574 @@
+use super::board::{self, Board, PieceType, Side, Square};
+use crate::{Castling, CastlingRights, State};
+use std::fmt;
+
+/// FEN of the standard starting position
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The six fields of a FEN record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenField {
+    Placement,
+    SideToMove,
+    Castling,
+    EnPassant,
+    HalfMoveClock,
+    FullMoveNumber,
+}
+
+impl fmt::Display for FenField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            FenField::Placement => "piece placement",
+            FenField::SideToMove => "side to move",
+            FenField::Castling => "castling rights",
+            FenField::EnPassant => "en passant square",
+            FenField::HalfMoveClock => "half-move clock",
+            FenField::FullMoveNumber => "full-move number",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Reasons a FEN string can be rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// A required field is missing
+    MissingField(FenField),
+    /// There is text after the full-move number
+    TrailingInput(String),
+    /// The placement does not have exactly eight ranks
+    RankCount(usize),
+    /// A rank does not describe exactly eight squares
+    RankLength { rank: usize, squares: usize },
+    /// A character in the placement is neither a piece nor a digit from 1 to 8
+    InvalidPiece { rank: usize, found: char },
+    /// A side does not have exactly one king
+    KingCount { side: Side, count: u32 },
+    /// A pawn stands on the first or eighth rank
+    PawnOnBackRank(Square),
+    /// The side to move is neither `w` nor `b`
+    InvalidSideToMove(String),
+    /// The castling field is malformed
+    InvalidCastling(String),
+    /// A castling right is set without its king and rook on their home squares
+    CastlingWithoutPieces(char),
+    /// The en passant field is not a square name or `-`
+    InvalidEnPassant(String),
+    /// The en passant square cannot follow a double pawn push
+    ImpossibleEnPassant(Square),
+    /// A clock is not a number in range
+    InvalidNumber { field: FenField, found: String },
+}
+
+impl FenError {
+    /// Returns the field that failed to parse
+    pub fn field(&self) -> FenField {
+        match self {
+            FenError::MissingField(field) | FenError::InvalidNumber { field, .. } => *field,
+            FenError::TrailingInput(_) => FenField::FullMoveNumber,
+            FenError::RankCount(_)
+            | FenError::RankLength { .. }
+            | FenError::InvalidPiece { .. }
+            | FenError::KingCount { .. }
+            | FenError::PawnOnBackRank(_) => FenField::Placement,
+            FenError::InvalidSideToMove(_) => FenField::SideToMove,
+            FenError::InvalidCastling(_) | FenError::CastlingWithoutPieces(_) => FenField::Castling,
+            FenError::InvalidEnPassant(_) | FenError::ImpossibleEnPassant(_) => FenField::EnPassant,
+        }
+    }
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid FEN {}: ", self.field())?;
+        match self {
+            FenError::MissingField(_) => write!(f, "field is missing"),
+            FenError::TrailingInput(rest) => write!(f, "unexpected trailing input '{}'", rest),
+            FenError::RankCount(count) => write!(f, "expected 8 ranks, found {}", count),
+            FenError::RankLength { rank, squares } => {
+                write!(f, "rank {} has {} squares, expected 8", rank, squares)
+            }
+            FenError::InvalidPiece { rank, found } => {
+                write!(f, "unexpected '{}' on rank {}", found, rank)
+            }
+            FenError::KingCount { side, count } => {
+                write!(f, "{:?} has {} kings, expected 1", side, count)
+            }
+            FenError::PawnOnBackRank(square) => {
+                write!(f, "pawn on back rank square {}", square_name(*square))
+            }
+            FenError::InvalidSideToMove(found) => {
+                write!(f, "expected 'w' or 'b', found '{}'", found)
+            }
+            FenError::InvalidCastling(found) => {
+                write!(f, "expected '-' or a subset of 'KQkq', found '{}'", found)
+            }
+            FenError::CastlingWithoutPieces(right) => {
+                write!(f, "'{}' is set but the king or rook has moved", right)
+            }
+            FenError::InvalidEnPassant(found) => {
+                write!(f, "expected '-' or a square, found '{}'", found)
+            }
+            FenError::ImpossibleEnPassant(square) => write!(
+                f,
+                "{} is not behind a pawn that just moved two squares",
+                square_name(*square)
+            ),
+            FenError::InvalidNumber { found, .. } => write!(f, "'{}' is not a valid number", found),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// Returns the algebraic name of a square, e.g. `e4`
+pub fn square_name(square: Square) -> String {
+    let file = (b'a' + (square % 8) as u8) as char;
+    let rank = (b'1' + (square / 8) as u8) as char;
+    format!("{}{}", file, rank)
+}
+
+/// Parses an algebraic square name, e.g. `e4`
+pub fn parse_square(name: &str) -> Option<Square> {
+    let bytes = name.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let file = bytes[0].wrapping_sub(b'a');
+    let rank = bytes[1].wrapping_sub(b'1');
+    if file < 8 && rank < 8 {
+        Some(rank as usize * 8 + file as usize)
+    } else {
+        None
+    }
+}
+
+/// Castling rights with the king and rook squares each one needs
+const CASTLING_SQUARES: [(char, u8, Side, Square, Square); 4] = [
+    ('K', Castling::WHITE_00, Side::White, board::E1, board::H1),
+    ('Q', Castling::WHITE_000, Side::White, board::E1, board::A1),
+    ('k', Castling::BLACK_00, Side::Black, board::E8, board::H8),
+    ('q', Castling::BLACK_000, Side::Black, board::E8, board::A8),
+];
+
+impl Board {
+    /// Parses a position in Forsyth-Edwards Notation
+    ///
+    /// The half-move clock and full-move number may be left out, in which case they default
+    /// to 0 and 1 as is common in EPD files.
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::pieces::board::{Board, Side};
+    ///
+    /// let board = Board::from_fen("8/8/8/4k3/8/8/8/4K3 b - - 12 40").unwrap();
+    /// assert_eq!(board.state().stm(), Side::Black);
+    /// assert!(Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let mut fields = fen.split_whitespace();
+        let mut board = Board::empty();
+
+        let placement = fields
+            .next()
+            .ok_or(FenError::MissingField(FenField::Placement))?;
+        parse_placement(&mut board, placement)?;
+
+        let stm = match fields.next() {
+            Some("w") => Side::White,
+            Some("b") => Side::Black,
+            Some(other) => return Err(FenError::InvalidSideToMove(other.to_string())),
+            None => return Err(FenError::MissingField(FenField::SideToMove)),
+        };
+
+        let castling = fields
+            .next()
+            .ok_or(FenError::MissingField(FenField::Castling))?;
+        let castling_rights = parse_castling(&board, castling)?;
+
+        let en_passant = fields
+            .next()
+            .ok_or(FenError::MissingField(FenField::EnPassant))?;
+        let en_passant_square = parse_en_passant(&board, stm, en_passant)?;
+
+        let half_move_counter = match fields.next() {
+            Some(clock) => parse_number(FenField::HalfMoveClock, clock)?,
+            None => 0,
+        };
+        let full_move_counter = match fields.next() {
+            Some(number) => match parse_number(FenField::FullMoveNumber, number)? {
+                0 => 1,
+                number => number,
+            },
+            None => 1,
+        };
+
+        let rest: Vec<&str> = fields.collect();
+        if !rest.is_empty() {
+            return Err(FenError::TrailingInput(rest.join(" ")));
+        }
+
+        *board.state_mut() = State {
+            castling_rights,
+            en_passant_square,
+            half_move_counter,
+            full_move_counter,
+            stm,
+        };
+        board.refresh_hash();
+
+        Ok(board)
+    }
+
+    /// Serializes the position to Forsyth-Edwards Notation
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::pieces::{board::Board, fen::START_FEN};
+    ///
+    /// assert_eq!(Board::default().to_fen(), START_FEN);
+    /// ```
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.piece_on(rank * 8 + file) {
+                    Some((side, piece)) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece.to_char(side));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        let state = self.state();
+        fen.push(' ');
+        fen.push(match state.stm {
+            Side::White => 'w',
+            Side::Black => 'b',
+        });
+
+        fen.push(' ');
+        if state.castling_rights.bits() == Castling::NO_CASTLING {
+            fen.push('-');
+        } else {
+            for (label, right, ..) in CASTLING_SQUARES {
+                if state.castling_rights.contains(right) {
+                    fen.push(label);
+                }
+            }
+        }
+
+        fen.push(' ');
+        match state.en_passant_square {
+            Some(square) => fen.push_str(&square_name(square)),
+            None => fen.push('-'),
+        }
+
+        fen.push_str(&format!(
+            " {} {}",
+            state.half_move_counter, state.full_move_counter
+        ));
+
+        fen
+    }
+}
+
+/// Fills the board from the piece placement field
+fn parse_placement(board: &mut Board, placement: &str) -> Result<(), FenError> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::RankCount(ranks.len()));
+    }
+
+    // FEN lists the ranks from the eighth down to the first
+    for (i, rank_text) in ranks.iter().enumerate() {
+        let rank = 7 - i;
+        let mut file = 0;
+        for c in rank_text.chars() {
+            if let Some(skip) = c.to_digit(10) {
+                if !(1..=8).contains(&skip) {
+                    return Err(FenError::InvalidPiece {
+                        rank: rank + 1,
+                        found: c,
+                    });
+                }
+                file += skip as usize;
+                continue;
+            }
+
+            let (side, piece) = PieceType::from_char(c).ok_or(FenError::InvalidPiece {
+                rank: rank + 1,
+                found: c,
+            })?;
+            if file < 8 {
+                board.put_piece(side, piece, rank * 8 + file);
+            }
+            file += 1;
+        }
+
+        if file != 8 {
+            return Err(FenError::RankLength {
+                rank: rank + 1,
+                squares: file,
+            });
+        }
+    }
+
+    for side in [Side::White, Side::Black] {
+        let count = board.pieces(side, PieceType::King).count_ones();
+        if count != 1 {
+            return Err(FenError::KingCount { side, count });
+        }
+    }
+
+    let pawns =
+        board.pieces(Side::White, PieceType::Pawn) | board.pieces(Side::Black, PieceType::Pawn);
+    let misplaced = pawns & (board::RANK_1 | board::RANK_8);
+    if misplaced != 0 {
+        return Err(FenError::PawnOnBackRank(
+            misplaced.trailing_zeros() as Square
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses the castling field and checks it against the king and rook placement
+fn parse_castling(board: &Board, castling: &str) -> Result<CastlingRights, FenError> {
+    let mut rights = CastlingRights::empty();
+    if castling == "-" {
+        return Ok(rights);
+    }
+
+    for c in castling.chars() {
+        let (_, right, side, king, rook) = CASTLING_SQUARES
+            .into_iter()
+            .find(|(label, ..)| *label == c)
+            .ok_or_else(|| FenError::InvalidCastling(castling.to_string()))?;
+        if rights.contains(right) {
+            return Err(FenError::InvalidCastling(castling.to_string()));
+        }
+        if board.piece_on(king) != Some((side, PieceType::King))
+            || board.piece_on(rook) != Some((side, PieceType::Rook))
+        {
+            return Err(FenError::CastlingWithoutPieces(c));
+        }
+        rights.insert(right);
+    }
+
+    Ok(rights)
+}
+
+/// Parses the en passant field and checks that a double pawn push could have produced it
+fn parse_en_passant(
+    board: &Board,
+    stm: Side,
+    en_passant: &str,
+) -> Result<Option<Square>, FenError> {
+    if en_passant == "-" {
+        return Ok(None);
+    }
+
+    let square = parse_square(en_passant)
+        .ok_or_else(|| FenError::InvalidEnPassant(en_passant.to_string()))?;
+
+    // The pawn that just moved stands one square past the target, seen from the side that moved
+    let (rank, pawn_square, origin) = match stm {
+        Side::White => (5, square.wrapping_sub(8), square + 8),
+        Side::Black => (2, square + 8, square.wrapping_sub(8)),
+    };
+    if square / 8 != rank
+        || board.piece_on(pawn_square) != Some((stm.opposite(), PieceType::Pawn))
+        || board.piece_on(square).is_some()
+        || board.piece_on(origin).is_some()
+    {
+        return Err(FenError::ImpossibleEnPassant(square));
+    }
+
+    Ok(Some(square))
+}
+
+/// Parses one of the two move counters
+fn parse_number<T: std::str::FromStr>(field: FenField, text: &str) -> Result<T, FenError> {
+    text.parse().map_err(|_| FenError::InvalidNumber {
+        field,
+        found: text.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_start_position() {
+        let board = Board::from_fen(START_FEN).unwrap();
+        assert_eq!(board, Board::default());
+        assert_eq!(board.to_fen(), START_FEN);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            "4k3/8/8/8/8/8/8/4K3 b - - 99 200",
+        ];
+        for fen in fens {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_state() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w Kq f6 0 3").unwrap();
+        let state = board.state();
+        assert_eq!(state.stm(), Side::White);
+        assert_eq!(state.en_passant_square(), Some(board::F6));
+        assert_eq!(state.half_move_counter(), 0);
+        assert_eq!(state.full_move_counter(), 3);
+        assert_eq!(
+            state.castling_rights().bits(),
+            Castling::WHITE_00 | Castling::BLACK_000
+        );
+        assert_eq!(
+            board.piece_on(board::E5),
+            Some((Side::White, PieceType::Pawn))
+        );
+        assert_eq!(
+            board.piece_on(board::D8),
+            Some((Side::Black, PieceType::Queen))
+        );
+    }
+
+    #[test]
+    fn test_missing_clocks() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+        assert_eq!(board.state().half_move_counter(), 0);
+        assert_eq!(board.state().full_move_counter(), 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let cases = [
+            ("", FenError::MissingField(FenField::Placement)),
+            (
+                "4k3/8/8/8/8/8/8/4K3",
+                FenError::MissingField(FenField::SideToMove),
+            ),
+            ("4k3/8/8/8/8/8/4K3 w - - 0 1", FenError::RankCount(7)),
+            (
+                "4k3/8/8/8/8/8/8/4K4 w - - 0 1",
+                FenError::RankLength {
+                    rank: 1,
+                    squares: 9,
+                },
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K2 w - - 0 1",
+                FenError::RankLength {
+                    rank: 1,
+                    squares: 7,
+                },
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K2x w - - 0 1",
+                FenError::InvalidPiece {
+                    rank: 1,
+                    found: 'x',
+                },
+            ),
+            (
+                "4k3/8/8/8/8/8/8/K3K3 w - - 0 1",
+                FenError::KingCount {
+                    side: Side::White,
+                    count: 2,
+                },
+            ),
+            (
+                "8/8/8/8/8/8/8/4K3 w - - 0 1",
+                FenError::KingCount {
+                    side: Side::Black,
+                    count: 0,
+                },
+            ),
+            (
+                "P3k3/8/8/8/8/8/8/4K3 w - - 0 1",
+                FenError::PawnOnBackRank(board::A8),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+                FenError::InvalidSideToMove("x".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/R3K2R w KX - 0 1",
+                FenError::InvalidCastling("KX".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/R3K2R w KK - 0 1",
+                FenError::InvalidCastling("KK".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w K - 0 1",
+                FenError::CastlingWithoutPieces('K'),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e9 0 1",
+                FenError::InvalidEnPassant("e9".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+                FenError::ImpossibleEnPassant(board::E6),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+                FenError::InvalidNumber {
+                    field: FenField::HalfMoveClock,
+                    found: "x".to_string(),
+                },
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 -1",
+                FenError::InvalidNumber {
+                    field: FenField::FullMoveNumber,
+                    found: "-1".to_string(),
+                },
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 1 extra",
+                FenError::TrailingInput("extra".to_string()),
+            ),
+        ];
+
+        for (fen, error) in cases {
+            assert_eq!(Board::from_fen(fen), Err(error), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_error_message() {
+        let error = Board::from_fen("4k3/8/8/8/8/8/8/4K4 w - - 0 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid FEN piece placement: rank 1 has 9 squares, expected 8"
+        );
+    }
+}
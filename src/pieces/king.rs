@@ -1,16 +1,16 @@
 use super::board;
 use super::traits::Piece;
-use std::{fmt, ops::*};
 
 ///King structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct King {
-    bboard: u64,
+    pub bboard: u64,
     side: board::Side,
 }
 
 impl King {
     /// Default white king board
-    pub const WHITE_DEFAULT: u64 = 0x8;
+    pub const WHITE_DEFAULT: u64 = 0x10;
     /// Default black king board
     pub const BLACK_DEFAULT: u64 = 0x1000000000000000;
     pub const KING_TABLE: [i32; 64] = [
@@ -33,69 +33,40 @@ impl King {
             },
         }
     }
+
+    /// Returns a bitboard of every square the king steps to
+    pub fn steps(&self) -> u64 {
+        board::north_one(self.bboard)
+            | board::northeast_one(self.bboard)
+            | board::east_one(self.bboard)
+            | board::southeast_one(self.bboard)
+            | board::south_one(self.bboard)
+            | board::southwest_one(self.bboard)
+            | board::west_one(self.bboard)
+            | board::northwest_one(self.bboard)
+    }
 }
 
 impl Piece for King {
-    /// Returns all moves
+    /// Returns all steps of the King, ignoring the other pieces on the board
     fn moves(&self) -> Vec<board::Move> {
-        let mut moves: Vec<board::Move> = Vec::new();
-
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: board::north_one(self.bboard).trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: board::west_one(self.bboard).trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: board::east_one(self.bboard).trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: board::south_one(self.bboard).trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: board::northeast_one(self.bboard).trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: board::northeast_one(self.bboard).trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: board::southwest_one(self.bboard).trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: board::southeast_one(self.bboard).trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-
-        moves
+        board::serialize_moves(
+            self.bboard.trailing_zeros() as usize,
+            self.steps(),
+            board::BoardFlags::king | self.side.flag(),
+        )
     }
 
     /// Returns all attacks
-    fn attacks(&self, blockers: u64) -> Vec<board::Move> {
+    fn attacks(&self, _blockers: u64) -> Vec<board::Move> {
         self.moves()
     }
 
     /// Returns the piece square value
     fn piece_square_value(&self) -> i32 {
         match self.side {
-            board::Side::White => return King::KING_TABLE[self.bboard.trailing_zeros() as usize],
-            board::Side::Black => {
-                return King::KING_TABLE[63 - self.bboard.trailing_zeros() as usize]
-            }
+            board::Side::White => King::KING_TABLE[self.bboard.trailing_zeros() as usize],
+            board::Side::Black => King::KING_TABLE[63 - self.bboard.trailing_zeros() as usize],
         }
     }
 
@@ -110,5 +81,17 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_king() {}
+    fn test_king() {
+        let king = King::new(board::Side::White);
+        let mut targets: Vec<usize> = king.moves().iter().map(|mv| mv.to).collect();
+        targets.sort();
+        assert_eq!(
+            targets,
+            [board::D1, board::F1, board::D2, board::E2, board::F2]
+        );
+        assert!(king
+            .moves()
+            .iter()
+            .all(|mv| mv.flags.contains(board::BoardFlags::king)));
+    }
 }
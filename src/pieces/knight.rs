@@ -1,7 +1,5 @@
 use super::board;
-use super::board::Side;
 use super::traits::Piece;
-use std::{fmt, ops};
 
 /// [short explanation of what the item does]
 ///
@@ -11,8 +9,9 @@ use std::{fmt, ops};
 ///
 /// # [OPTIONAL: more explanations and code examples in case some specific
 /// # cases have to be explained in details]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KnightSet {
-    bboards: Vec<Knight>,
+    pub bboard: u64,
     side: board::Side,
 }
 
@@ -20,9 +19,11 @@ impl Piece for KnightSet {
     /// Returns a bitboard of all valid moves in a KnightSet
     fn moves(&self) -> Vec<board::Move> {
         let mut moves: Vec<board::Move> = Vec::new();
-
-        for knight in self.bboards.iter() {
+        let mut set = self.bboard;
+        while set != 0 {
+            let knight = Knight::new(set & set.wrapping_neg(), self.side);
             moves.extend(knight.moves());
+            set &= set - 1;
         }
 
         moves
@@ -31,9 +32,11 @@ impl Piece for KnightSet {
     /// Returns a bitboard of all valid attacks in a KnightSet
     fn attacks(&self, blockers: u64) -> Vec<board::Move> {
         let mut moves: Vec<board::Move> = Vec::new();
-
-        for knight in self.bboards.iter() {
+        let mut set = self.bboard;
+        while set != 0 {
+            let knight = Knight::new(set & set.wrapping_neg(), self.side);
             moves.extend(knight.attacks(blockers));
+            set &= set - 1;
         }
 
         moves
@@ -45,39 +48,29 @@ impl Piece for KnightSet {
 
     /// Returns the board
     fn board(&self) -> u64 {
-        let mut board: u64 = 0;
-        for knight in self.bboards {
-            board &= knight.board();
-        }
-        board
+        self.bboard
     }
 }
 
 impl KnightSet {
     /// Creates a new KnightSet of the given side
     pub fn new(side: board::Side) -> Self {
-        let mut bboards: Vec<Knight> = Vec::new();
-
-        match side {
-            board::Side::White => {
-                bboards.push(Knight::new(board::POSITION_ARRAY[board::B1], side));
-                bboards.push(Knight::new(board::POSITION_ARRAY[board::G1], side));
-            }
-            board::Side::Black => {
-                bboards.push(Knight::new(board::POSITION_ARRAY[board::B8], side));
-                bboards.push(Knight::new(board::POSITION_ARRAY[board::G8], side));
-            }
-        }
+        let bboard = match side {
+            board::Side::White => Knight::WHITE_DEFAULT,
+            board::Side::Black => Knight::BLACK_DEFAULT,
+        };
 
-        KnightSet { bboards, side }
+        KnightSet { bboard, side }
     }
 
     /// Evaluates the KnightSet
     pub fn evaluate(&self) -> i32 {
         let mut score = 0;
-
-        for knight in self.bboards {
+        let mut set = self.bboard;
+        while set != 0 {
+            let knight = Knight::new(set & set.wrapping_neg(), self.side);
             score += 3 * knight.piece_square_value();
+            set &= set - 1;
         }
 
         score
@@ -85,6 +78,7 @@ impl KnightSet {
 }
 
 /// Knight structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Knight {
     bboard: u64,
     side: board::Side,
@@ -102,24 +96,11 @@ impl Knight {
 
     /// Creates a new Knight of the given side
     pub fn new(pos: u64, side: board::Side) -> Self {
-        match side {
-            board::Side::White => {
-                return Knight {
-                    bboard: Knight::WHITE_DEFAULT,
-                    side: Side::White,
-                }
-            }
-            board::Side::Black => {
-                return Knight {
-                    bboard: Knight::BLACK_DEFAULT,
-                    side: Side::Black,
-                }
-            }
-        }
+        Knight { bboard: pos, side }
     }
 
     pub fn north_north_east(&self) -> u64 {
-        (self.bboard << 15) & !board::A_FILE
+        (self.bboard << 17) & !board::A_FILE
     }
 
     pub fn north_east_east(&self) -> u64 {
@@ -135,7 +116,7 @@ impl Knight {
     }
 
     pub fn north_north_west(&self) -> u64 {
-        (self.bboard << 17) & !board::H_FILE
+        (self.bboard << 15) & !board::H_FILE
     }
 
     pub fn north_west_west(&self) -> u64 {
@@ -145,65 +126,43 @@ impl Knight {
     pub fn south_south_west(&self) -> u64 {
         (self.bboard >> 17) & !board::H_FILE
     }
+
+    pub fn south_west_west(&self) -> u64 {
+        (self.bboard >> 10) & !(board::G_FILE | board::H_FILE)
+    }
+
+    /// Returns a bitboard of every square the knight jumps to
+    pub fn jumps(&self) -> u64 {
+        self.north_north_east()
+            | self.north_east_east()
+            | self.south_east_east()
+            | self.south_south_east()
+            | self.north_north_west()
+            | self.north_west_west()
+            | self.south_south_west()
+            | self.south_west_west()
+    }
 }
 
 impl Piece for Knight {
-    /// Returns a bitboard of all valid moves in a Knight
+    /// Returns all jumps of the Knight, ignoring the other pieces on the board
     fn moves(&self) -> Vec<board::Move> {
-        let mut moves: Vec<board::Move> = Vec::new();
-
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.north_north_east().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.north_east_east().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.south_east_east().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.south_south_east().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.north_north_west().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.north_west_west().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.south_south_west().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-
-        moves
+        board::serialize_moves(
+            self.bboard.trailing_zeros() as usize,
+            self.jumps(),
+            board::BoardFlags::knight | self.side.flag(),
+        )
     }
 
     /// Returns a bitboard of all valid attacks
-    fn attacks(&self, blockers: u64) -> Vec<board::Move> {
+    fn attacks(&self, _blockers: u64) -> Vec<board::Move> {
         self.moves()
     }
 
     fn piece_square_value(&self) -> i32 {
         match self.side {
-            board::Side::White => {
-                return Knight::KNIGHT_TABLE[self.bboard.trailing_zeros() as usize]
-            }
-            board::Side::Black => {
-                return Knight::KNIGHT_TABLE[63 - self.bboard.trailing_zeros() as usize]
-            }
+            board::Side::White => Knight::KNIGHT_TABLE[self.bboard.trailing_zeros() as usize],
+            board::Side::Black => Knight::KNIGHT_TABLE[63 - self.bboard.trailing_zeros() as usize],
         }
     }
 
@@ -218,5 +177,17 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_knight() {}
+    fn test_knight() {
+        let knight = Knight::new(board::POSITION_ARRAY[board::D4], board::Side::White);
+        assert_eq!(
+            knight.jumps(),
+            crate::pieces::attacks::knight_attacks(board::D4)
+        );
+        assert_eq!(knight.moves().len(), 8);
+
+        let knight = Knight::new(board::POSITION_ARRAY[board::H8], board::Side::Black);
+        let mut targets: Vec<usize> = knight.moves().iter().map(|mv| mv.to).collect();
+        targets.sort();
+        assert_eq!(targets, [board::G6, board::F7]);
+    }
 }
@@ -0,0 +1,312 @@
+use super::attacks::{ray_bishop_attacks, ray_rook_attacks};
+use super::board::{Square, A_FILE, H_FILE, POSITION_ARRAY, RANK_1, RANK_8};
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+use super::pext;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+
+/// Ways of turning an occupancy into a slider attack table index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Multiply by a magic number and shift, works on every CPU
+    Magic,
+    /// Extract the relevant bits with BMI2 `pext`, needs the `pext` feature and a BMI2 CPU
+    Pext,
+}
+
+/// Set once the PEXT backend is selected, which only happens when the CPU supports BMI2
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+static USE_PEXT: AtomicBool = AtomicBool::new(false);
+
+/// Magic lookup data for one square
+#[derive(Debug, Clone, Copy, Default)]
+struct Magic {
+    /// Relevant blockers, the attack ray without the board edge
+    mask: u64,
+    /// Multiplier mapping every blocker subset to a distinct index
+    magic: u64,
+    /// Number of bits the product is shifted right by
+    shift: u32,
+    /// Start of this square's entries in the shared attack table
+    offset: usize,
+}
+
+impl Magic {
+    /// Returns the table index of the given occupancy
+    fn index(&self, occupancy: u64) -> usize {
+        self.offset + ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+/// Magic numbers and attack tables for one kind of slider
+struct MagicTable {
+    magics: [Magic; 64],
+    attacks: Vec<u64>,
+}
+
+/// Rook tables, built the first time a rook attack is looked up
+static ROOK_TABLE: LazyLock<MagicTable> = LazyLock::new(|| MagicTable::new(ray_rook_attacks));
+
+/// Bishop tables, built the first time a bishop attack is looked up
+static BISHOP_TABLE: LazyLock<MagicTable> = LazyLock::new(|| MagicTable::new(ray_bishop_attacks));
+
+/// Seed of the magic number search, fixed so the tables are the same on every run
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Small xorshift generator for the magic number search
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Magic candidates work best with few bits set
+    fn sparse(&mut self) -> u64 {
+        self.next() & self.next() & self.next()
+    }
+}
+
+/// Returns the squares whose occupancy changes the attacks from `square`
+///
+/// The last square of each ray is always attacked whether it is occupied or not, so the
+/// board edges are dropped unless the slider stands on them.
+pub fn relevant_mask(square: Square, attacks: fn(Square, u64) -> u64) -> u64 {
+    let bit = POSITION_ARRAY[square];
+    let mut edges = 0;
+    for edge in [RANK_1, RANK_8, A_FILE, H_FILE] {
+        if bit & edge == 0 {
+            edges |= edge;
+        }
+    }
+    attacks(square, 0) & !edges
+}
+
+impl MagicTable {
+    /// Searches magic numbers for every square and fills the attack table
+    fn new(attacks: fn(Square, u64) -> u64) -> Self {
+        let mut rng = Xorshift(SEED);
+        let mut magics = [Magic::default(); 64];
+        let mut table = Vec::new();
+        let mut occupancies = Vec::with_capacity(4096);
+        let mut reference = Vec::with_capacity(4096);
+
+        for (square, magic) in magics.iter_mut().enumerate() {
+            let mask = relevant_mask(square, attacks);
+            let bits = mask.count_ones();
+
+            // Enumerate every subset of the mask with the carry-rippler trick
+            occupancies.clear();
+            reference.clear();
+            let mut subset: u64 = 0;
+            loop {
+                occupancies.push(subset);
+                reference.push(attacks(square, subset));
+                subset = subset.wrapping_sub(mask) & mask;
+                if subset == 0 {
+                    break;
+                }
+            }
+
+            *magic = Magic {
+                mask,
+                magic: 0,
+                shift: 64 - bits,
+                offset: table.len(),
+            };
+            table.resize(table.len() + (1 << bits), 0);
+
+            let mut used = vec![0u32; 1 << bits];
+            let mut attempt = 0;
+            'search: loop {
+                magic.magic = rng.sparse();
+                // Good magics spread the highest mask bits into the top byte of the product
+                if (mask.wrapping_mul(magic.magic) >> 56).count_ones() < 6 {
+                    continue;
+                }
+
+                attempt += 1;
+                for (occupancy, attack) in occupancies.iter().zip(reference.iter()) {
+                    let index = magic.index(*occupancy);
+                    let slot = index - magic.offset;
+                    if used[slot] != attempt {
+                        used[slot] = attempt;
+                        table[index] = *attack;
+                    } else if table[index] != *attack {
+                        continue 'search;
+                    }
+                }
+                break;
+            }
+        }
+
+        MagicTable {
+            magics,
+            attacks: table,
+        }
+    }
+
+    fn attacks(&self, square: Square, occupancy: u64) -> u64 {
+        self.attacks[self.magics[square].index(occupancy)]
+    }
+}
+
+/// Builds the rook and bishop tables now instead of on the first lookup
+///
+/// Selects the PEXT backend when it is compiled in and the CPU supports it.
+pub fn init() {
+    LazyLock::force(&ROOK_TABLE);
+    LazyLock::force(&BISHOP_TABLE);
+    set_backend(Backend::Pext);
+}
+
+/// Returns true if the PEXT backend is compiled in and the CPU supports BMI2
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+pub fn pext_available() -> bool {
+    is_x86_feature_detected!("bmi2")
+}
+
+/// Returns true if the PEXT backend is compiled in and the CPU supports BMI2
+#[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+pub fn pext_available() -> bool {
+    false
+}
+
+/// Returns the backend used by the attack lookups
+pub fn backend() -> Backend {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    if USE_PEXT.load(Ordering::Relaxed) {
+        return Backend::Pext;
+    }
+    Backend::Magic
+}
+
+/// Switches the attack lookups to `backend`, returns false and keeps the current one if it
+/// is not available
+pub fn set_backend(backend: Backend) -> bool {
+    match backend {
+        Backend::Magic => {
+            #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+            USE_PEXT.store(false, Ordering::Relaxed);
+            true
+        }
+        Backend::Pext => {
+            if !pext_available() {
+                return false;
+            }
+            #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+            {
+                LazyLock::force(&pext::ROOK_TABLE);
+                LazyLock::force(&pext::BISHOP_TABLE);
+                USE_PEXT.store(true, Ordering::Relaxed);
+            }
+            true
+        }
+    }
+}
+
+/// Returns the squares a rook on `square` attacks given the occupancy
+pub fn rook_attacks(square: Square, occupancy: u64) -> u64 {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    if USE_PEXT.load(Ordering::Relaxed) {
+        // SAFETY: the flag is only set after BMI2 support was detected
+        return unsafe { pext::rook_attacks(square, occupancy) };
+    }
+    ROOK_TABLE.attacks(square, occupancy)
+}
+
+/// Returns the squares a bishop on `square` attacks given the occupancy
+pub fn bishop_attacks(square: Square, occupancy: u64) -> u64 {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    if USE_PEXT.load(Ordering::Relaxed) {
+        // SAFETY: the flag is only set after BMI2 support was detected
+        return unsafe { pext::bishop_attacks(square, occupancy) };
+    }
+    BISHOP_TABLE.attacks(square, occupancy)
+}
+
+/// Returns the squares a queen on `square` attacks given the occupancy
+pub fn queen_attacks(square: Square, occupancy: u64) -> u64 {
+    rook_attacks(square, occupancy) | bishop_attacks(square, occupancy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pieces::board::{get_ray, Ray};
+
+    /// Walks each ray with `get_ray`, keeping the squares with no blocker in front of them
+    fn slow_attacks(square: Square, occupancy: u64, rays: &[Ray]) -> u64 {
+        let bit = POSITION_ARRAY[square];
+        let mut attacks = 0;
+        for ray in rays {
+            let full = get_ray(bit, ray.clone()) & !bit;
+            let mut squares = full;
+            while squares != 0 {
+                let target = POSITION_ARRAY[squares.trailing_zeros() as usize];
+                let between = full & !get_ray(target, ray.clone());
+                if between & occupancy == 0 {
+                    attacks |= target;
+                }
+                squares &= squares - 1;
+            }
+        }
+        attacks
+    }
+
+    const ROOK_RAYS: [Ray; 4] = [Ray::North, Ray::East, Ray::South, Ray::West];
+    const BISHOP_RAYS: [Ray; 4] = [
+        Ray::NorthEast,
+        Ray::SouthEast,
+        Ray::SouthWest,
+        Ray::NorthWest,
+    ];
+
+    /// Checks every table entry of every square against the slow ray walk
+    fn check_table(table: &MagicTable, rays: &[Ray]) {
+        for square in 0..64 {
+            let mask = table.magics[square].mask;
+            let mut subset: u64 = 0;
+            loop {
+                assert_eq!(
+                    table.attacks(square, subset),
+                    slow_attacks(square, subset, rays),
+                    "square {} occupancy {:#x}",
+                    square,
+                    subset
+                );
+                subset = subset.wrapping_sub(mask) & mask;
+                if subset == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rook_table() {
+        check_table(&ROOK_TABLE, &ROOK_RAYS);
+    }
+
+    #[test]
+    fn test_bishop_table() {
+        check_table(&BISHOP_TABLE, &BISHOP_RAYS);
+    }
+
+    #[test]
+    fn test_irrelevant_occupancy() {
+        // Pieces outside the mask, including edge squares behind blockers, change nothing
+        for square in 0..64 {
+            let occupancy = 0x8100_0000_0000_0081 | POSITION_ARRAY[square ^ 9];
+            assert_eq!(
+                queen_attacks(square, occupancy),
+                slow_attacks(square, occupancy, &ROOK_RAYS)
+                    | slow_attacks(square, occupancy, &BISHOP_RAYS)
+            );
+        }
+    }
+}
@@ -1,8 +1,16 @@
+pub mod attacks;
 pub mod bishop;
 pub mod board;
+pub mod fen;
 pub mod king;
 pub mod knight;
+pub mod magic;
+pub mod movegen;
 pub mod pawn;
+mod perft;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+mod pext;
 pub mod queen;
 pub mod rook;
+pub mod see;
 pub mod traits;
@@ -0,0 +1,843 @@
+use super::attacks::{
+    bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rook_attacks,
+    BETWEEN, LINE,
+};
+use super::board::{
+    self, north_one, northeast_one, northwest_one, south_one, southeast_one, southwest_one, Board,
+    BoardFlags, Move, PieceType, Side, Square, C1, C8, E1, E8, G1, G8, POSITION_ARRAY,
+};
+use super::traits::Piece;
+use crate::Castling;
+
+/// Pieces a pawn can promote to, queen first
+pub const PROMOTIONS: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Knight,
+    PieceType::Rook,
+    PieceType::Bishop,
+];
+
+impl Board {
+    /// Returns the king square of the given side
+    pub fn king_square(&self, side: Side) -> Square {
+        self.pieces(side, PieceType::King).trailing_zeros() as Square
+    }
+
+    /// Returns every piece of either side that attacks `square` given the occupancy
+    pub fn attackers_to(&self, square: Square, occupancy: u64) -> u64 {
+        let mut attackers = 0;
+        for side in [Side::White, Side::Black] {
+            let player = self.player(side);
+            let diagonal = player.pieces(PieceType::Bishop) | player.pieces(PieceType::Queen);
+            let straight = player.pieces(PieceType::Rook) | player.pieces(PieceType::Queen);
+            attackers |= pawn_attacks(side.opposite(), square) & player.pieces(PieceType::Pawn)
+                | knight_attacks(square) & player.pieces(PieceType::Knight)
+                | king_attacks(square) & player.pieces(PieceType::King)
+                | bishop_attacks(square, occupancy) & diagonal
+                | rook_attacks(square, occupancy) & straight;
+        }
+        attackers
+    }
+
+    /// Returns true if any piece of `side` attacks `square`
+    pub fn is_attacked(&self, square: Square, side: Side, occupancy: u64) -> bool {
+        self.attackers_to(square, occupancy) & self.player(side).board() != 0
+    }
+
+    /// Returns the pieces giving check to the side to move
+    pub fn checkers(&self) -> u64 {
+        let us = self.state().stm();
+        self.attackers_to(self.king_square(us), self.occupancy())
+            & self.player(us.opposite()).board()
+    }
+
+    /// Returns true if the side to move is in check
+    pub fn is_in_check(&self) -> bool {
+        self.checkers() != 0
+    }
+
+    /// Returns the pieces of `side` pinned to their own king
+    pub fn pinned(&self, side: Side) -> u64 {
+        let king = self.king_square(side);
+        let them = self.player(side.opposite());
+        let occupancy = self.occupancy();
+        let mut snipers = rook_attacks(king, 0)
+            & (them.pieces(PieceType::Rook) | them.pieces(PieceType::Queen))
+            | bishop_attacks(king, 0)
+                & (them.pieces(PieceType::Bishop) | them.pieces(PieceType::Queen));
+
+        let mut pinned = 0;
+        while snipers != 0 {
+            let sniper = snipers.trailing_zeros() as usize;
+            let blockers = BETWEEN[king][sniper] & occupancy;
+            if blockers.count_ones() == 1 {
+                pinned |= blockers & self.player(side).board();
+            }
+            snipers &= snipers - 1;
+        }
+        pinned
+    }
+
+    /// Returns every legal move in the position
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::pieces::board::Board;
+    ///
+    /// assert_eq!(Board::default().legal_moves().len(), 20);
+    /// ```
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = MoveList::new();
+        let us = self.state().stm();
+        let them = us.opposite();
+        let own = self.player(us).board();
+        let enemy = self.player(them).board();
+        let occupancy = own | enemy;
+        let king = self.king_square(us);
+        let checkers = self.checkers();
+
+        // The king may go to any square that is not attacked once it has left its square
+        let without_king = occupancy & !POSITION_ARRAY[king];
+        let mut targets = king_attacks(king) & !own;
+        while targets != 0 {
+            let to = targets.trailing_zeros() as usize;
+            if !self.is_attacked(to, them, without_king) {
+                self.push_move(&mut moves, king, to, PieceType::King, BoardFlags::empty());
+            }
+            targets &= targets - 1;
+        }
+
+        // In double check only the king can move
+        if checkers.count_ones() > 1 {
+            return moves.to_vec();
+        }
+
+        // Other pieces have to capture the checker or block the check
+        let evasion = match checkers {
+            0 => board::FULL,
+            _ => {
+                let checker = checkers.trailing_zeros() as usize;
+                checkers | BETWEEN[king][checker]
+            }
+        };
+        let pinned = self.pinned(us);
+        let player = self.player(us);
+
+        for piece in [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+        ] {
+            let mut set = player.pieces(piece);
+            while set != 0 {
+                let from = set.trailing_zeros() as usize;
+                let mut targets = match piece {
+                    PieceType::Knight => knight_attacks(from),
+                    PieceType::Bishop => bishop_attacks(from, occupancy),
+                    PieceType::Rook => rook_attacks(from, occupancy),
+                    _ => bishop_attacks(from, occupancy) | rook_attacks(from, occupancy),
+                } & !own
+                    & evasion;
+                if pinned & POSITION_ARRAY[from] != 0 {
+                    targets &= LINE[king][from];
+                }
+                while targets != 0 {
+                    let to = targets.trailing_zeros() as usize;
+                    self.push_move(&mut moves, from, to, piece, BoardFlags::empty());
+                    targets &= targets - 1;
+                }
+                set &= set - 1;
+            }
+        }
+
+        self.pawn_moves(&mut moves, evasion, pinned);
+
+        if checkers == 0 {
+            self.castling_moves(&mut moves);
+        }
+
+        moves.to_vec()
+    }
+
+    /// Adds a move, setting the color, piece and capture flags
+    fn push_move(
+        &self,
+        moves: &mut MoveList,
+        from: Square,
+        to: Square,
+        piece: PieceType,
+        flags: BoardFlags,
+    ) {
+        let us = self.state().stm();
+        let mut flags = flags | piece.flag() | us.flag();
+        if self.player(us.opposite()).board() & POSITION_ARRAY[to] != 0 {
+            flags |= BoardFlags::capture;
+        }
+        moves.push(Move::new(from, to, flags));
+    }
+
+    /// Adds every legal pawn move landing on `evasion`
+    fn pawn_moves(&self, moves: &mut MoveList, evasion: u64, pinned: u64) {
+        let us = self.state().stm();
+        let them = us.opposite();
+        let occupancy = self.occupancy();
+        let enemy = self.player(them).board();
+        let king = self.king_square(us);
+        let (forward, start_rank, last_rank): (isize, u64, u64) = match us {
+            Side::White => (8, board::RANK_2, board::RANK_8),
+            Side::Black => (-8, board::RANK_7, board::RANK_1),
+        };
+
+        let mut pawns = self.pieces(us, PieceType::Pawn);
+        while pawns != 0 {
+            let from = pawns.trailing_zeros() as usize;
+            let bit = POSITION_ARRAY[from];
+            let allowed = match pinned & bit {
+                0 => evasion,
+                _ => evasion & LINE[king][from],
+            };
+
+            let mut targets = pawn_attacks(us, from) & enemy;
+            let push = (from as isize + forward) as usize;
+            if occupancy & POSITION_ARRAY[push] == 0 {
+                targets |= POSITION_ARRAY[push];
+                let double = (push as isize + forward) as usize;
+                if bit & start_rank != 0 && occupancy & POSITION_ARRAY[double] == 0 {
+                    targets |= POSITION_ARRAY[double];
+                }
+            }
+            targets &= allowed;
+
+            while targets != 0 {
+                let to = targets.trailing_zeros() as usize;
+                if POSITION_ARRAY[to] & last_rank != 0 {
+                    for promotion in PROMOTIONS {
+                        self.push_move(moves, from, to, promotion, BoardFlags::promotion);
+                    }
+                } else {
+                    self.push_move(moves, from, to, PieceType::Pawn, BoardFlags::empty());
+                }
+                targets &= targets - 1;
+            }
+
+            if let Some(to) = self.state().en_passant_square() {
+                if pawn_attacks(us, from) & POSITION_ARRAY[to] != 0
+                    && self.is_legal_en_passant(from, to)
+                {
+                    let flags = BoardFlags::capture | BoardFlags::passant;
+                    self.push_move(moves, from, to, PieceType::Pawn, flags);
+                }
+            }
+
+            pawns &= pawns - 1;
+        }
+    }
+
+    /// Returns true if capturing en passant does not leave the king attacked
+    ///
+    /// Both the capturing and the captured pawn leave their squares, which can uncover a
+    /// slider along the rank, so the check is done on the resulting occupancy.
+    fn is_legal_en_passant(&self, from: Square, to: Square) -> bool {
+        let us = self.state().stm();
+        let them = self.player(us.opposite());
+        let captured = match us {
+            Side::White => to - 8,
+            Side::Black => to + 8,
+        };
+        let occupancy = (self.occupancy() ^ POSITION_ARRAY[from] ^ POSITION_ARRAY[captured])
+            | POSITION_ARRAY[to];
+        let king = self.king_square(us);
+        let diagonal = them.pieces(PieceType::Bishop) | them.pieces(PieceType::Queen);
+        let straight = them.pieces(PieceType::Rook) | them.pieces(PieceType::Queen);
+        let remaining = !POSITION_ARRAY[captured];
+
+        bishop_attacks(king, occupancy) & diagonal & remaining == 0
+            && rook_attacks(king, occupancy) & straight & remaining == 0
+            && knight_attacks(king) & them.pieces(PieceType::Knight) == 0
+            && pawn_attacks(us, king) & them.pieces(PieceType::Pawn) & remaining == 0
+    }
+
+    /// Adds castling moves when the king is not in check
+    fn castling_moves(&self, moves: &mut MoveList) {
+        let us = self.state().stm();
+        let them = us.opposite();
+        let occupancy = self.occupancy();
+        let rights = self.state().castling_rights();
+        let (king, king_side, queen_side) = match us {
+            Side::White => (E1, Castling::WHITE_00, Castling::WHITE_000),
+            Side::Black => (E8, Castling::BLACK_00, Castling::BLACK_000),
+        };
+        let (short, long) = match us {
+            Side::White => (G1, C1),
+            Side::Black => (G8, C8),
+        };
+
+        // The rook's path must be empty and the king may not pass an attacked square
+        if rights.contains(king_side)
+            && BETWEEN[king][king + 3] & occupancy == 0
+            && !self.is_attacked(king + 1, them, occupancy)
+            && !self.is_attacked(short, them, occupancy)
+        {
+            self.push_move(moves, king, short, PieceType::King, BoardFlags::castle_king);
+        }
+        if rights.contains(queen_side)
+            && BETWEEN[king][king - 4] & occupancy == 0
+            && !self.is_attacked(king - 1, them, occupancy)
+            && !self.is_attacked(long, them, occupancy)
+        {
+            self.push_move(moves, king, long, PieceType::King, BoardFlags::castle_queen);
+        }
+    }
+}
+
+/// Most moves any chess position can have is 218, so this never overflows
+pub const MAX_MOVES: usize = 256;
+
+/// A fixed capacity list of moves that lives on the stack
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveList {
+    /// Creates an empty move list
+    pub fn new() -> Self {
+        MoveList {
+            moves: [Move::NULL; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    /// Adds a move to the end of the list
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    /// Removes every move from the list
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl std::ops::Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl std::ops::DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+/// Which moves a [`MoveGen`] produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Captures, en passant and every promotion, for quiescence search
+    Captures,
+    /// Non-capturing moves that are not promotions, including castling
+    Quiets,
+    /// Moves that may get the side to move out of check
+    Evasions,
+    /// Evasions when in check, otherwise captures followed by quiets
+    All,
+}
+
+/// Pseudo-legal move generator
+///
+/// Moves are pseudo-legal: they may still leave the own king attacked through a pin, or move
+/// the king into check, so each one has to pass [`Board::is_legal`] before it is played. The
+/// `Captures` and `Quiets` stages assume the side to move is not in check.
+///
+/// Example
+/// ```
+/// use rust_chess::pieces::board::Board;
+/// use rust_chess::pieces::movegen::{MoveGen, MoveList, Stage};
+///
+/// let board = Board::default();
+/// let mut moves = MoveList::new();
+/// MoveGen::new(&board).generate(Stage::Quiets, &mut moves);
+/// assert_eq!(moves.len(), 20);
+/// ```
+pub struct MoveGen<'a> {
+    board: &'a Board,
+}
+
+impl<'a> MoveGen<'a> {
+    /// Creates a generator for the side to move
+    pub fn new(board: &'a Board) -> Self {
+        MoveGen { board }
+    }
+
+    /// Appends the moves of the given stage to the list
+    pub fn generate(&self, stage: Stage, moves: &mut MoveList) {
+        let board = self.board;
+        let us = board.state().stm();
+        let own = board.player(us).board();
+        let enemy = board.player(us.opposite()).board();
+        let empty = !(own | enemy);
+
+        match stage {
+            Stage::Captures => {
+                self.piece_moves(moves, enemy);
+                self.pawn_captures(moves, enemy, board::FULL);
+            }
+            Stage::Quiets => {
+                self.piece_moves(moves, empty);
+                self.pawn_pushes(moves, empty & !board::RANK_1 & !board::RANK_8);
+                board.castling_moves(moves);
+            }
+            Stage::Evasions => self.evasions(moves),
+            Stage::All => {
+                if board.is_in_check() {
+                    self.evasions(moves);
+                } else {
+                    self.generate(Stage::Captures, moves);
+                    self.generate(Stage::Quiets, moves);
+                }
+            }
+        }
+    }
+
+    /// Adds king moves and, against a single checker, captures and blocks
+    fn evasions(&self, moves: &mut MoveList) {
+        let board = self.board;
+        let us = board.state().stm();
+        let own = board.player(us).board();
+        let king = board.king_square(us);
+        let checkers = board.checkers();
+
+        let mut targets = king_attacks(king) & !own;
+        while targets != 0 {
+            let to = targets.trailing_zeros() as usize;
+            board.push_move(moves, king, to, PieceType::King, BoardFlags::empty());
+            targets &= targets - 1;
+        }
+
+        if checkers.count_ones() != 1 {
+            return;
+        }
+        let block = checkers | BETWEEN[king][checkers.trailing_zeros() as usize];
+        let empty = !board.occupancy();
+
+        self.piece_moves(moves, block);
+        self.pawn_captures(moves, checkers, block);
+        self.pawn_pushes(moves, block & empty & !board::RANK_1 & !board::RANK_8);
+    }
+
+    /// Adds knight, bishop, rook, queen and king moves landing on `targets`
+    fn piece_moves(&self, moves: &mut MoveList, targets: u64) {
+        let board = self.board;
+        let us = board.state().stm();
+        let player = board.player(us);
+        let occupancy = board.occupancy();
+        let in_check = board.is_in_check();
+
+        for piece in [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+            PieceType::King,
+        ] {
+            // King evasions are added separately, without the block mask
+            if piece == PieceType::King && in_check {
+                continue;
+            }
+            let mut set = player.pieces(piece);
+            while set != 0 {
+                let from = set.trailing_zeros() as usize;
+                let mut attacks = match piece {
+                    PieceType::Knight => knight_attacks(from),
+                    PieceType::Bishop => bishop_attacks(from, occupancy),
+                    PieceType::Rook => rook_attacks(from, occupancy),
+                    PieceType::Queen => queen_attacks(from, occupancy),
+                    _ => king_attacks(from),
+                } & targets;
+                while attacks != 0 {
+                    let to = attacks.trailing_zeros() as usize;
+                    board.push_move(moves, from, to, piece, BoardFlags::empty());
+                    attacks &= attacks - 1;
+                }
+                set &= set - 1;
+            }
+        }
+    }
+
+    /// Adds pawn captures of `enemy`, en passant and every promotion landing on `promote`
+    fn pawn_captures(&self, moves: &mut MoveList, enemy: u64, promote: u64) {
+        let board = self.board;
+        let us = board.state().stm();
+        let pawns = board.pieces(us, PieceType::Pawn);
+        let empty = !board.occupancy();
+
+        // Shift the whole pawn set at once, remembering how far each shift moved a pawn
+        let (push, west, east, last_rank) = match us {
+            Side::White => (
+                north_one(pawns),
+                northwest_one(pawns),
+                northeast_one(pawns),
+                board::RANK_8,
+            ),
+            Side::Black => (
+                south_one(pawns),
+                southwest_one(pawns),
+                southeast_one(pawns),
+                board::RANK_1,
+            ),
+        };
+        let (push_step, west_step, east_step): (isize, isize, isize) = match us {
+            Side::White => (8, 7, 9),
+            Side::Black => (-8, -9, -7),
+        };
+
+        for (targets, step) in [
+            (push & empty & last_rank & promote, push_step),
+            (west & enemy, west_step),
+            (east & enemy, east_step),
+        ] {
+            let mut targets = targets;
+            while targets != 0 {
+                let to = targets.trailing_zeros() as usize;
+                let from = (to as isize - step) as usize;
+                if POSITION_ARRAY[to] & last_rank != 0 {
+                    for promotion in PROMOTIONS {
+                        board.push_move(moves, from, to, promotion, BoardFlags::promotion);
+                    }
+                } else {
+                    board.push_move(moves, from, to, PieceType::Pawn, BoardFlags::empty());
+                }
+                targets &= targets - 1;
+            }
+        }
+
+        if let Some(to) = board.state().en_passant_square() {
+            let mut attackers = pawn_attacks(us.opposite(), to) & pawns;
+            while attackers != 0 {
+                let from = attackers.trailing_zeros() as usize;
+                let flags = BoardFlags::capture | BoardFlags::passant;
+                board.push_move(moves, from, to, PieceType::Pawn, flags);
+                attackers &= attackers - 1;
+            }
+        }
+    }
+
+    /// Adds single and double pawn pushes landing on `targets`
+    fn pawn_pushes(&self, moves: &mut MoveList, targets: u64) {
+        let board = self.board;
+        let us = board.state().stm();
+        let pawns = board.pieces(us, PieceType::Pawn);
+        let empty = !board.occupancy();
+
+        let (single, double, step): (u64, u64, isize) = match us {
+            Side::White => {
+                let single = north_one(pawns) & empty;
+                (single, north_one(single & board::RANK_3) & empty, 8)
+            }
+            Side::Black => {
+                let single = south_one(pawns) & empty;
+                (single, south_one(single & board::RANK_6) & empty, -8)
+            }
+        };
+
+        for (targets, distance) in [(single & targets, step), (double & targets, 2 * step)] {
+            let mut targets = targets;
+            while targets != 0 {
+                let to = targets.trailing_zeros() as usize;
+                let from = (to as isize - distance) as usize;
+                if POSITION_ARRAY[to] & (board::RANK_1 | board::RANK_8) != 0 {
+                    for promotion in PROMOTIONS {
+                        board.push_move(moves, from, to, promotion, BoardFlags::promotion);
+                    }
+                } else {
+                    board.push_move(moves, from, to, PieceType::Pawn, BoardFlags::empty());
+                }
+                targets &= targets - 1;
+            }
+        }
+    }
+}
+
+impl Board {
+    /// Returns true if a pseudo-legal move from [`MoveGen`] leaves the own king safe
+    pub fn is_legal(&self, mv: Move) -> bool {
+        let us = self.state().stm();
+        let king = self.king_square(us);
+
+        if mv.is_en_passant() {
+            return self.is_legal_en_passant(mv.from, mv.to);
+        }
+        if mv.from == king {
+            // Castling already checked the squares the king crosses
+            let occupancy = self.occupancy() & !POSITION_ARRAY[king];
+            return mv.is_castle() || !self.is_attacked(mv.to, us.opposite(), occupancy);
+        }
+
+        // Only a piece on a line with its king can be pinned
+        LINE[king][mv.from] == 0
+            || self.pinned(us) & POSITION_ARRAY[mv.from] == 0
+            || LINE[king][mv.from] & POSITION_ARRAY[mv.to] != 0
+    }
+
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::pieces::board::Board;
+    ///
+    /// assert_eq!(Board::default().perft(2), 400);
+    /// ```
+    pub fn perft(&mut self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for mv in moves {
+            let undo = self.make_move(mv);
+            nodes += self.perft(depth - 1);
+            self.unmake_move(mv, undo);
+        }
+        nodes
+    }
+
+    /// Returns the perft count below each legal move, `depth` counting the move itself
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::pieces::board::Board;
+    ///
+    /// let divide = Board::default().divide(2);
+    /// assert_eq!(divide.len(), 20);
+    /// assert!(divide.iter().all(|(_, nodes)| *nodes == 20));
+    /// ```
+    pub fn divide(&mut self, depth: usize) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let undo = self.make_move(mv);
+                let nodes = self.perft(depth - 1);
+                self.unmake_move(mv, undo);
+                (mv, nodes)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn perft(board: &mut Board, depth: usize) -> u64 {
+        board.perft(depth)
+    }
+
+    fn count(fen: &str, depth: usize) -> u64 {
+        perft(&mut Board::from_fen(fen).unwrap(), depth)
+    }
+
+    #[test]
+    fn test_start_position() {
+        let mut board = Board::default();
+        assert_eq!(perft(&mut board, 1), 20);
+        assert_eq!(perft(&mut board, 2), 400);
+        assert_eq!(perft(&mut board, 3), 8902);
+    }
+
+    #[test]
+    fn test_divide() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let mut board = Board::from_fen(fen).unwrap();
+        let divide = board.divide(2);
+        assert_eq!(divide.len(), 48);
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+        let castle = divide.iter().find(|(mv, _)| mv.to_string() == "e1g1");
+        assert_eq!(castle.map(|(_, nodes)| *nodes), Some(43));
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_kiwipete() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(count(fen, 1), 48);
+        assert_eq!(count(fen, 2), 2039);
+    }
+
+    #[test]
+    fn test_pins_and_en_passant() {
+        // The e.p. capture would expose the king along the fifth rank
+        let fen = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+        assert_eq!(count(fen, 1), 14);
+        assert_eq!(count(fen, 3), 2812);
+        let board = Board::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 1").unwrap();
+        assert!(board.legal_moves().iter().all(|mv| !mv.is_en_passant()));
+    }
+
+    #[test]
+    fn test_checks() {
+        // Double check from the rook and knight leaves only king moves
+        let board = Board::from_fen("4r1k1/8/8/8/8/3n4/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(board.checkers().count_ones(), 2);
+        assert!(board
+            .legal_moves()
+            .iter()
+            .all(|mv| mv.piece() == PieceType::King && !mv.is_castle()));
+
+        // A single check can be blocked or the checker captured
+        let board = Board::from_fen("4k3/8/8/b7/8/8/8/RN2K3 w Q - 0 1").unwrap();
+        let mut moves: Vec<String> = board.legal_moves().iter().map(Move::to_string).collect();
+        moves.sort();
+        assert_eq!(
+            moves,
+            ["a1a5", "b1c3", "b1d2", "e1d1", "e1e2", "e1f1", "e1f2"]
+        );
+    }
+
+    #[test]
+    fn test_castling() {
+        // The bishop on a6 covers f1, so only queen side castling is legal
+        let board = Board::from_fen("r3k2r/8/b7/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let castles: Vec<Move> = board
+            .legal_moves()
+            .into_iter()
+            .filter(Move::is_castle)
+            .collect();
+        assert_eq!(castles.len(), 1);
+        assert_eq!(castles[0].to, C1);
+        assert!(castles[0].flags.contains(BoardFlags::castle_queen));
+
+        // b1 may be attacked when castling long, it is only crossed by the rook
+        let board = Board::from_fen("1r2k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        assert_eq!(
+            board
+                .legal_moves()
+                .iter()
+                .filter(|mv| mv.is_castle())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_promotions() {
+        let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotions: Vec<Move> = board
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| mv.promotion().is_some())
+            .collect();
+        assert_eq!(promotions.len(), 8);
+        assert_eq!(promotions.iter().filter(|mv| mv.is_capture()).count(), 4);
+        assert!(promotions
+            .iter()
+            .any(|mv| mv.promotion() == Some(PieceType::Knight) && mv.to == board::B8));
+    }
+
+    /// Counts leaf nodes using the staged generator and the legality filter
+    fn staged_perft(board: &mut Board, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let mut moves = MoveList::new();
+        MoveGen::new(board).generate(Stage::All, &mut moves);
+        let mut nodes = 0;
+        for &mv in moves.iter() {
+            if !board.is_legal(mv) {
+                continue;
+            }
+            let undo = board.make_move(mv);
+            nodes += staged_perft(board, depth - 1);
+            board.unmake_move(mv, undo);
+        }
+        nodes
+    }
+
+    #[test]
+    fn test_staged_generation() {
+        let fens = [
+            crate::pieces::fen::START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ];
+        for fen in fens {
+            let mut board = Board::from_fen(fen).unwrap();
+            assert_eq!(staged_perft(&mut board, 3), perft(&mut board, 3), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_stages() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let generator = MoveGen::new(&board);
+
+        let mut captures = MoveList::new();
+        generator.generate(Stage::Captures, &mut captures);
+        assert!(captures
+            .iter()
+            .all(|mv| mv.is_capture() || mv.promotion().is_some()));
+
+        let mut quiets = MoveList::new();
+        generator.generate(Stage::Quiets, &mut quiets);
+        assert!(quiets
+            .iter()
+            .all(|mv| !mv.is_capture() && mv.promotion().is_none()));
+
+        let legal = |list: &MoveList| list.iter().filter(|mv| board.is_legal(**mv)).count();
+        assert_eq!(legal(&captures), 8);
+        assert_eq!(legal(&captures) + legal(&quiets), 48);
+
+        // Quiet promotions belong to the capture stage
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mut captures = MoveList::new();
+        MoveGen::new(&board).generate(Stage::Captures, &mut captures);
+        assert_eq!(captures.len(), 4);
+    }
+
+    #[test]
+    fn test_evasions() {
+        // Only the king steps and the block on d2 or c3 answer the bishop check
+        let board = Board::from_fen("4k3/8/8/b7/8/8/8/RN2K3 w Q - 0 1").unwrap();
+        let mut evasions = MoveList::new();
+        MoveGen::new(&board).generate(Stage::Evasions, &mut evasions);
+        let mut moves: Vec<String> = evasions
+            .iter()
+            .filter(|mv| board.is_legal(**mv))
+            .map(Move::to_string)
+            .collect();
+        moves.sort();
+        let mut legal: Vec<String> = board.legal_moves().iter().map(Move::to_string).collect();
+        legal.sort();
+        assert_eq!(moves, legal);
+    }
+
+    #[test]
+    fn test_flags() {
+        for mv in Board::default().legal_moves() {
+            assert!(mv.flags.contains(BoardFlags::white));
+            assert!(!mv.is_capture());
+            assert!(mv.piece() == PieceType::Pawn || mv.piece() == PieceType::Knight);
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use super::board::{self, BoardFlags};
+use super::board;
 use super::traits::Piece;
-use std::{fmt, ops::*};
 
 /// A complete set of black and white pawns
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PawnSet {
-    bboards: Vec<Pawn>,
+    pub bboard: u64,
     side: board::Side,
 }
 
@@ -12,9 +12,11 @@ impl Piece for PawnSet {
     /// Returns a bitboard of all valid moves in a PawnSet
     fn moves(&self) -> Vec<board::Move> {
         let mut moves: Vec<board::Move> = Vec::new();
-        let mut set: u64 = 0x00;
-        for pawn in self.bboards.iter() {
+        let mut set = self.bboard;
+        while set != 0 {
+            let pawn = Pawn::new(set & set.wrapping_neg(), self.side);
             moves.extend(pawn.moves());
+            set &= set - 1;
         }
 
         moves
@@ -23,9 +25,11 @@ impl Piece for PawnSet {
     /// Returns a bitboard of all valid attacks in a PawnSet
     fn attacks(&self, blockers: u64) -> Vec<board::Move> {
         let mut moves: Vec<board::Move> = Vec::new();
-        let mut set: u64 = 0x00;
-        for pawn in self.bboards.iter() {
+        let mut set = self.bboard;
+        while set != 0 {
+            let pawn = Pawn::new(set & set.wrapping_neg(), self.side);
             moves.extend(pawn.attacks(blockers));
+            set &= set - 1;
         }
 
         moves
@@ -37,40 +41,29 @@ impl Piece for PawnSet {
 
     /// Returns the board
     fn board(&self) -> u64 {
-        let mut board: u64 = 0;
-        for pawn in self.bboards {
-            board &= pawn.board();
-        }
-        board
+        self.bboard
     }
 }
 
 impl PawnSet {
     /// Creates a new PawnSet of the given side
     pub fn new(side: board::Side) -> Self {
-        let mut bboards: Vec<Pawn> = Vec::new();
-
-        match side {
-            board::Side::White => {
-                for i in board::A2..board::A3 {
-                    bboards.push(Pawn::new(board::POSITION_ARRAY[i], side));
-                }
-            }
-            board::Side::Black => {
-                for i in board::A7..board::A8 {
-                    bboards.push(Pawn::new(board::POSITION_ARRAY[i], side));
-                }
-            }
-        }
+        let bboard = match side {
+            board::Side::White => Pawn::WHITE_DEFAULT,
+            board::Side::Black => Pawn::BLACK_DEFAULT,
+        };
 
-        PawnSet { bboards, side }
+        PawnSet { bboard, side }
     }
 
     /// Evaluates the PawnSet
     pub fn evaluate(&self) -> i32 {
         let mut score = 0;
-        for pawn in self.bboards {
-            score += 1 * pawn.piece_square_value();
+        let mut set = self.bboard;
+        while set != 0 {
+            let pawn = Pawn::new(set & set.wrapping_neg(), self.side);
+            score += pawn.piece_square_value();
+            set &= set - 1;
         }
 
         score
@@ -78,53 +71,40 @@ impl PawnSet {
 }
 
 /// Pawn structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Pawn {
     pub bboard: u64,
     pub side: board::Side,
 }
 
 impl Piece for Pawn {
-    /// Returns a bitboard of all valid moves
+    /// Returns the single push and, from the starting rank, the double push
     fn moves(&self) -> Vec<board::Move> {
-        let mut moves: Vec<board::Move> = Vec::new();
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.push().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.double_push().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
+        let mut targets = self.push();
+        if self.bboard & self.start_rank() != 0 {
+            targets |= self.double_push();
+        }
 
-        moves
+        board::serialize_moves(
+            self.bboard.trailing_zeros() as usize,
+            targets,
+            board::BoardFlags::pawn | self.side.flag(),
+        )
     }
 
-    /// Returns a bitboard of all valid attacks
-    fn attacks(&self, blockers: u64) -> Vec<board::Move> {
-        let mut moves: Vec<board::Move> = Vec::new();
-
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.west_attacks().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-        moves.push(board::Move {
-            from: self.bboard.trailing_zeros() as usize,
-            to: self.east_attacks().trailing_zeros() as usize,
-            flags: board::BoardFlags::empty(),
-        });
-
-        moves
+    /// Returns both diagonal captures
+    fn attacks(&self, _blockers: u64) -> Vec<board::Move> {
+        board::serialize_moves(
+            self.bboard.trailing_zeros() as usize,
+            self.west_attacks() | self.east_attacks(),
+            board::BoardFlags::pawn | board::BoardFlags::capture | self.side.flag(),
+        )
     }
 
     fn piece_square_value(&self) -> i32 {
         match self.side {
-            board::Side::White => return Pawn::PAWN_TABLE[self.bboard.trailing_zeros() as usize],
-            board::Side::Black => {
-                return Pawn::PAWN_TABLE[63 - self.bboard.trailing_zeros() as usize]
-            }
+            board::Side::White => Pawn::PAWN_TABLE[self.bboard.trailing_zeros() as usize],
+            board::Side::Black => Pawn::PAWN_TABLE[63 - self.bboard.trailing_zeros() as usize],
         }
     }
 
@@ -161,15 +141,24 @@ impl Pawn {
 
     /// Returns a bitboard of a side relevant pawn double push
     pub fn double_push(&self) -> u64 {
-        self.push();
-        self.push();
-        self.bboard
+        match self.side {
+            board::Side::White => board::north_one(board::north_one(self.bboard)),
+            board::Side::Black => board::south_one(board::south_one(self.bboard)),
+        }
+    }
+
+    /// Returns the rank pawns of this side start on
+    pub fn start_rank(&self) -> u64 {
+        match self.side {
+            board::Side::White => board::RANK_2,
+            board::Side::Black => board::RANK_7,
+        }
     }
 
     /// Returns a bitboard of valid west attacks
     pub fn west_attacks(&self) -> u64 {
         match self.side {
-            board::Side::White => board::northeast_one(self.bboard),
+            board::Side::White => board::northwest_one(self.bboard),
             board::Side::Black => board::southwest_one(self.bboard),
         }
     }
@@ -190,7 +179,20 @@ mod test {
     #[test]
     fn test_pawn() {
         let pawn = Pawn::new(0x1, board::Side::White);
-        assert_eq!(pawn.push(), 0x100 as u64);
-        assert_eq!(pawn.double_push(), 0x10000 as u64);
+        assert_eq!(pawn.push(), 0x100_u64);
+        assert_eq!(pawn.double_push(), 0x10000_u64);
+    }
+
+    #[test]
+    fn test_pawn_moves() {
+        let pawn = Pawn::new(board::POSITION_ARRAY[board::E2], board::Side::White);
+        assert_eq!(pawn.moves().len(), 2);
+        let pawn = Pawn::new(board::POSITION_ARRAY[board::E3], board::Side::White);
+        assert_eq!(pawn.moves().len(), 1);
+
+        let pawn = Pawn::new(board::POSITION_ARRAY[board::A7], board::Side::Black);
+        assert_eq!(pawn.west_attacks(), 0);
+        assert_eq!(pawn.east_attacks(), board::POSITION_ARRAY[board::B6]);
+        assert_eq!(pawn.attacks(0).len(), 1);
     }
 }
@@ -0,0 +1,74 @@
+//! Perft regression suite over the positions in `perft.epd`
+//!
+//! Depths up to `QUICK_NODES` leaf nodes run with `cargo test`, the rest with
+//! `cargo test --release -- --ignored`.
+
+#[cfg(test)]
+mod test {
+    use crate::pieces::board::Board;
+
+    /// Depths with more leaf nodes than this only run in the ignored deep test
+    const QUICK_NODES: u64 = 100_000;
+
+    /// One position of the suite with its expected node counts
+    struct Case {
+        fen: &'static str,
+        counts: Vec<(usize, u64)>,
+    }
+
+    /// Parses the suite, skipping blank lines and `#` comments
+    fn cases() -> Vec<Case> {
+        include_str!("perft.epd")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let mut fields = line.split(';');
+                let fen = fields.next().unwrap().trim();
+                let counts = fields
+                    .map(|field| {
+                        let (depth, nodes) = field
+                            .trim()
+                            .strip_prefix('D')
+                            .and_then(|field| field.split_once(' '))
+                            .unwrap_or_else(|| panic!("invalid depth field {:?}", field));
+                        (depth.parse().unwrap(), nodes.trim().parse().unwrap())
+                    })
+                    .collect();
+                Case { fen, counts }
+            })
+            .collect()
+    }
+
+    /// Runs every depth whose expected count passes `filter`
+    fn run(filter: impl Fn(u64) -> bool) {
+        for case in cases() {
+            let mut board = Board::from_fen(case.fen).unwrap();
+            for &(depth, nodes) in case.counts.iter().filter(|(_, nodes)| filter(*nodes)) {
+                assert_eq!(board.perft(depth), nodes, "{} depth {}", case.fen, depth);
+            }
+            assert_eq!(board.to_fen(), case.fen);
+        }
+    }
+
+    #[test]
+    fn test_suite_parses() {
+        let cases = cases();
+        assert!(cases.len() >= 6);
+        for case in cases {
+            assert!(Board::from_fen(case.fen).is_ok(), "{}", case.fen);
+            assert!(!case.counts.is_empty(), "{}", case.fen);
+        }
+    }
+
+    #[test]
+    fn test_perft_quick() {
+        run(|nodes| nodes <= QUICK_NODES);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_perft_deep() {
+        run(|nodes| nodes > QUICK_NODES);
+    }
+}
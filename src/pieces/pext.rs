@@ -0,0 +1,120 @@
+use super::attacks::{ray_bishop_attacks, ray_rook_attacks};
+use super::board::Square;
+use super::magic::relevant_mask;
+use std::arch::x86_64::_pext_u64;
+use std::sync::LazyLock;
+
+/// Attack tables indexed by extracting the relevant occupancy bits with `pext`
+pub(super) struct PextTable {
+    masks: [u64; 64],
+    offsets: [usize; 64],
+    attacks: Vec<u64>,
+}
+
+/// Rook tables, built the first time the PEXT backend is selected
+pub(super) static ROOK_TABLE: LazyLock<PextTable> =
+    LazyLock::new(|| PextTable::new(ray_rook_attacks));
+
+/// Bishop tables, built the first time the PEXT backend is selected
+pub(super) static BISHOP_TABLE: LazyLock<PextTable> =
+    LazyLock::new(|| PextTable::new(ray_bishop_attacks));
+
+impl PextTable {
+    /// Fills the attack table of every square
+    ///
+    /// The carry-rippler walk visits the subsets of a mask in the order of their extracted
+    /// bits, so the entries can be pushed one after another without calling `pext`.
+    fn new(attacks: fn(Square, u64) -> u64) -> Self {
+        let mut masks = [0; 64];
+        let mut offsets = [0; 64];
+        let mut table = Vec::new();
+
+        for square in 0..64 {
+            let mask = relevant_mask(square, attacks);
+            masks[square] = mask;
+            offsets[square] = table.len();
+
+            let mut subset: u64 = 0;
+            loop {
+                table.push(attacks(square, subset));
+                subset = subset.wrapping_sub(mask) & mask;
+                if subset == 0 {
+                    break;
+                }
+            }
+        }
+
+        PextTable {
+            masks,
+            offsets,
+            attacks: table,
+        }
+    }
+
+    /// Looks up the attacks from `square`
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support BMI2.
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    unsafe fn attacks(&self, square: Square, occupancy: u64) -> u64 {
+        self.attacks[self.offsets[square] + _pext_u64(occupancy, self.masks[square]) as usize]
+    }
+}
+
+/// Returns the squares a rook on `square` attacks given the occupancy
+///
+/// # Safety
+///
+/// The CPU must support BMI2.
+#[inline]
+#[target_feature(enable = "bmi2")]
+pub(super) unsafe fn rook_attacks(square: Square, occupancy: u64) -> u64 {
+    ROOK_TABLE.attacks(square, occupancy)
+}
+
+/// Returns the squares a bishop on `square` attacks given the occupancy
+///
+/// # Safety
+///
+/// The CPU must support BMI2.
+#[inline]
+#[target_feature(enable = "bmi2")]
+pub(super) unsafe fn bishop_attacks(square: Square, occupancy: u64) -> u64 {
+    BISHOP_TABLE.attacks(square, occupancy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tables() {
+        if !is_x86_feature_detected!("bmi2") {
+            return;
+        }
+        let tables = [
+            (&*ROOK_TABLE, ray_rook_attacks as fn(Square, u64) -> u64),
+            (&*BISHOP_TABLE, ray_bishop_attacks),
+        ];
+        for (table, attacks) in tables {
+            for square in 0..64 {
+                let mask = table.masks[square];
+                let mut subset: u64 = 0;
+                loop {
+                    // Set the squares outside the mask too, they must not change the index
+                    let occupancy = subset | !mask;
+                    assert_eq!(
+                        unsafe { table.attacks(square, occupancy) },
+                        attacks(square, occupancy)
+                    );
+                    subset = subset.wrapping_sub(mask) & mask;
+                    if subset == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,6 @@
-use super::board::{
-    self, RAY_EAST, RAY_NORTH, RAY_NORTH_EAST, RAY_NORTH_WEST, RAY_SOUTH, RAY_SOUTH_EAST,
-    RAY_SOUTH_WEST, RAY_WEST,
-};
+use super::attacks;
+use super::board;
 use super::traits::Piece;
-use std::{fmt, ops::*};
 
 ///Queen structure
 ///
@@ -11,14 +8,15 @@ use std::{fmt, ops::*};
 /// ```
 /// ```
 ///
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Queen {
-    bboard: u64,
+    pub bboard: u64,
     side: board::Side,
 }
 
 impl Queen {
     /// Default white queen board
-    pub const WHITE_DEFAULT: u64 = 0x10;
+    pub const WHITE_DEFAULT: u64 = 0x8;
     /// Default black queen board
     pub const BLACK_DEFAULT: u64 = 0x800000000000000;
     pub const QUEEN_TABLE: [i32; 64] = [
@@ -40,46 +38,51 @@ impl Queen {
             },
         }
     }
+
+    /// Evaluates every queen on the board
+    pub fn evaluate(&self) -> i32 {
+        let mut score = 0;
+        let mut set = self.bboard;
+        while set != 0 {
+            let index = set.trailing_zeros() as usize;
+            score += match self.side {
+                board::Side::White => Queen::QUEEN_TABLE[index],
+                board::Side::Black => Queen::QUEEN_TABLE[63 - index],
+            };
+            set &= set - 1;
+        }
+
+        score
+    }
 }
 
 impl Piece for Queen {
-    /// Returns all moves
+    /// Returns all moves of every queen on an empty board
     fn moves(&self) -> Vec<board::Move> {
-        let mut moves: Vec<board::Move> = Vec::new();
+        self.attacks(0)
+    }
 
-        let index = self.bboard.trailing_zeros() as usize;
-        let mut set = RAY_WEST[index]
-            | RAY_NORTH[index]
-            | RAY_EAST[index]
-            | RAY_SOUTH[index]
-            | RAY_NORTH_EAST[index]
-            | RAY_NORTH_WEST[index]
-            | RAY_SOUTH_EAST[index]
-            | RAY_SOUTH_WEST[index];
+    /// Returns all attacks of every queen, stopping at the first blocker on each ray
+    fn attacks(&self, blockers: u64) -> Vec<board::Move> {
+        let mut moves: Vec<board::Move> = Vec::new();
+        let mut set = self.bboard;
         while set != 0 {
-            let to = set.trailing_zeros();
-            moves.push(board::Move {
-                from: self.bboard.trailing_zeros() as usize,
-                to: to as usize,
-                flags: board::BoardFlags::empty(),
-            });
+            let from = set.trailing_zeros() as usize;
+            moves.extend(board::serialize_moves(
+                from,
+                attacks::queen_attacks(from, blockers),
+                board::BoardFlags::queen | self.side.flag(),
+            ));
             set &= set - 1;
         }
 
         moves
     }
 
-    /// Returns all attacks
-    fn attacks(&self, pos: u64) -> Vec<board::Move> {
-        self.moves()
-    }
-
     fn piece_square_value(&self) -> i32 {
         match self.side {
-            board::Side::White => return Queen::QUEEN_TABLE[self.bboard.trailing_zeros() as usize],
-            board::Side::Black => {
-                return Queen::QUEEN_TABLE[63 - self.bboard.trailing_zeros() as usize]
-            }
+            board::Side::White => Queen::QUEEN_TABLE[self.bboard.trailing_zeros() as usize],
+            board::Side::Black => Queen::QUEEN_TABLE[63 - self.bboard.trailing_zeros() as usize],
         }
     }
 
@@ -94,5 +97,12 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_queen() {}
+    fn test_queen() {
+        let queen = Queen {
+            bboard: board::POSITION_ARRAY[board::D4],
+            side: board::Side::White,
+        };
+        assert_eq!(queen.moves().len(), 27);
+        assert_eq!(queen.attacks(board::POSITION_ARRAY[board::E4]).len(), 24);
+    }
 }
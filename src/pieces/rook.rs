@@ -1,33 +1,38 @@
-use super::board::{self, RAY_EAST, RAY_NORTH, RAY_SOUTH, RAY_WEST};
+use super::attacks;
+use super::board;
 use super::traits::Piece;
-use std::{fmt, ops};
 
 /// A complete set of black and white Rooks
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RookSet {
-    /// Rook bitboards
-    bboards: Vec<Rook>,
+    /// Rook bitboard
+    pub bboard: u64,
     /// The side of the board
     side: board::Side,
 }
 
 impl Piece for RookSet {
-    /// Returns a bitboard of all valid Rook Moves
+    /// Returns a bitboard of all valid moves in a RookSet
     fn moves(&self) -> Vec<board::Move> {
         let mut moves: Vec<board::Move> = Vec::new();
-
-        for rook in self.bboards.iter() {
+        let mut set = self.bboard;
+        while set != 0 {
+            let rook = Rook::new(set & set.wrapping_neg(), self.side);
             moves.extend(rook.moves());
+            set &= set - 1;
         }
 
         moves
     }
 
-    /// Returns a bitboard of all valid Rook attacks
+    /// Returns a bitboard of all valid attacks in a RookSet
     fn attacks(&self, blockers: u64) -> Vec<board::Move> {
         let mut moves: Vec<board::Move> = Vec::new();
-
-        for rook in self.bboards.iter() {
+        let mut set = self.bboard;
+        while set != 0 {
+            let rook = Rook::new(set & set.wrapping_neg(), self.side);
             moves.extend(rook.attacks(blockers));
+            set &= set - 1;
         }
 
         moves
@@ -39,39 +44,29 @@ impl Piece for RookSet {
 
     /// Returns the board
     fn board(&self) -> u64 {
-        let mut board: u64 = 0;
-        for rook in self.bboards {
-            board &= rook.board();
-        }
-        board
+        self.bboard
     }
 }
 
 impl RookSet {
     /// Creates a new RookSet of the given side
     pub fn new(side: board::Side) -> Self {
-        let mut bboards: Vec<Rook> = Vec::new();
-
-        match side {
-            board::Side::White => {
-                bboards.push(Rook::new(board::POSITION_ARRAY[board::A1], side));
-                bboards.push(Rook::new(board::POSITION_ARRAY[board::H1], side));
-            }
-            board::Side::Black => {
-                bboards.push(Rook::new(board::POSITION_ARRAY[board::A8], side));
-                bboards.push(Rook::new(board::POSITION_ARRAY[board::H8], side));
-            }
-        }
+        let bboard = match side {
+            board::Side::White => Rook::WHITE_DEFAULT,
+            board::Side::Black => Rook::BLACK_DEFAULT,
+        };
 
-        RookSet { bboards, side }
+        RookSet { bboard, side }
     }
 
     /// Returns the evaluation of the RookSet
     pub fn evaluate(&self) -> i32 {
         let mut score = 0;
-
-        for rook in self.bboards {
+        let mut set = self.bboard;
+        while set != 0 {
+            let rook = Rook::new(set & set.wrapping_neg(), self.side);
             score += 5 * rook.piece_square_value();
+            set &= set - 1;
         }
 
         score
@@ -79,6 +74,7 @@ impl RookSet {
 }
 
 /// Rook structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rook {
     bboard: u64,
     side: board::Side,
@@ -99,42 +95,30 @@ impl Rook {
 
     /// Creates a new Rook of the given side
     pub fn new(pos: u64, side: board::Side) -> Self {
-        todo!()
+        Rook { bboard: pos, side }
     }
 }
 
 impl Piece for Rook {
-    /// Return a bitboard of all valid moves
+    /// Returns all moves of the Rook on an empty board
     fn moves(&self) -> Vec<board::Move> {
-        let mut moves: Vec<board::Move> = Vec::new();
-
-        let index = board::bitscan(self.bboard).unwrap() as usize;
-        let mut set = RAY_NORTH[index] | RAY_EAST[index] | RAY_SOUTH[index] | RAY_WEST[index];
-
-        while set != 0 {
-            let to = set.trailing_zeros();
-            moves.push(board::Move {
-                from: self.bboard.trailing_zeros() as usize,
-                to: to as usize,
-                flags: board::BoardFlags::empty(),
-            });
-            set &= set - 1;
-        }
-
-        moves
+        self.attacks(0)
     }
 
-    /// Return a bitboard of all vlalid attacks
+    /// Returns all attacks of the Rook, stopping at the first blocker on each ray
     fn attacks(&self, blockers: u64) -> Vec<board::Move> {
-        todo!()
+        let from = self.bboard.trailing_zeros() as usize;
+        board::serialize_moves(
+            from,
+            attacks::rook_attacks(from, blockers),
+            board::BoardFlags::rook | self.side.flag(),
+        )
     }
 
     fn piece_square_value(&self) -> i32 {
         match self.side {
-            board::Side::White => return Rook::ROOK_TABLE[self.bboard.trailing_zeros() as usize],
-            board::Side::Black => {
-                return Rook::ROOK_TABLE[63 - self.bboard.trailing_zeros() as usize]
-            }
+            board::Side::White => Rook::ROOK_TABLE[self.bboard.trailing_zeros() as usize],
+            board::Side::Black => Rook::ROOK_TABLE[63 - self.bboard.trailing_zeros() as usize],
         }
     }
 
@@ -149,5 +133,11 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_rook() {}
+    fn test_rook() {
+        let rook = Rook::new(board::POSITION_ARRAY[board::A1], board::Side::White);
+        assert_eq!(rook.moves().len(), 14);
+        // A blocker on a4 is attacked but hides a5 to a8
+        let blockers = board::POSITION_ARRAY[board::A1 + 24];
+        assert_eq!(rook.attacks(blockers).len(), 10);
+    }
 }
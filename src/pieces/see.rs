@@ -0,0 +1,220 @@
+use super::attacks::{bishop_attacks, rook_attacks};
+use super::board::{Board, Move, PieceType, Side, Square, POSITION_ARRAY};
+use super::traits::Piece;
+
+/// Longest exchange on one square: every piece of both sides takes part
+const MAX_EXCHANGE: usize = 32;
+
+impl Board {
+    /// Returns the value of the piece a move takes and what a promotion adds to it
+    fn capture_gain(&self, mv: Move) -> i32 {
+        let captured = if mv.is_en_passant() {
+            PieceType::Pawn.value()
+        } else if mv.is_capture() {
+            self.piece_on(mv.to).map_or(0, |(_, piece)| piece.value())
+        } else {
+            0
+        };
+        captured
+            + mv.promotion()
+                .map_or(0, |piece| piece.value() - PieceType::Pawn.value())
+    }
+
+    /// Returns the value of the piece standing on the target square once a move is played
+    fn moved_value(mv: Move) -> i32 {
+        mv.promotion().unwrap_or(mv.piece()).value()
+    }
+
+    /// Returns the least valuable piece of `side` among `attackers`
+    fn least_valuable(&self, side: Side, attackers: u64) -> Option<(PieceType, Square)> {
+        PieceType::ALL.into_iter().find_map(|piece| {
+            let set = self.pieces(side, piece) & attackers;
+            (set != 0).then(|| (piece, set.trailing_zeros() as Square))
+        })
+    }
+
+    /// Static exchange evaluation: the material the side to move wins by playing `mv` when
+    /// both sides keep recapturing on the target square with their least valuable piece, each
+    /// free to stop whenever going on would lose material
+    ///
+    /// Sliders hidden behind a piece that takes part join the exchange once it has moved.
+    /// Pins are not taken into account, and a king only recaptures when the square is no
+    /// longer attacked. Castling scores 0.
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::pieces::board::Board;
+    ///
+    /// // The pawn on d6 is defended, taking it loses the queen for a pawn
+    /// let board = Board::from_fen("4k3/2p5/3p4/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    /// let mv = *board.legal_moves().iter().find(|mv| mv.to_string() == "d1d6").unwrap();
+    /// assert_eq!(board.see(mv), 100 - 900);
+    /// ```
+    pub fn see(&self, mv: Move) -> i32 {
+        if mv.is_castle() {
+            return 0;
+        }
+
+        let to = mv.to;
+        let mut occupancy = self.occupancy() ^ POSITION_ARRAY[mv.from];
+        if mv.is_en_passant() {
+            let square = match mv.side() {
+                Side::White => to - 8,
+                Side::Black => to + 8,
+            };
+            occupancy ^= POSITION_ARRAY[square];
+        }
+
+        let bishops =
+            |side: Side| self.pieces(side, PieceType::Bishop) | self.pieces(side, PieceType::Queen);
+        let rooks =
+            |side: Side| self.pieces(side, PieceType::Rook) | self.pieces(side, PieceType::Queen);
+        let diagonal = bishops(Side::White) | bishops(Side::Black);
+        let straight = rooks(Side::White) | rooks(Side::Black);
+
+        // gains[n] is what the side making the n-th capture has won if the exchange ends there
+        let mut gains = [0; MAX_EXCHANGE];
+        gains[0] = self.capture_gain(mv);
+        let mut on_square = Self::moved_value(mv);
+        let mut attackers = self.attackers_to(to, occupancy) & occupancy;
+        let mut side = mv.side().opposite();
+        let mut depth = 0;
+
+        while let Some((piece, from)) = self.least_valuable(side, attackers) {
+            // The king cannot take while the square is still defended
+            if piece == PieceType::King && attackers & self.player(side.opposite()).board() != 0 {
+                break;
+            }
+
+            depth += 1;
+            gains[depth] = on_square - gains[depth - 1];
+            on_square = piece.value();
+
+            // Moving the piece may uncover a slider behind it
+            occupancy ^= POSITION_ARRAY[from];
+            if matches!(
+                piece,
+                PieceType::Pawn | PieceType::Bishop | PieceType::Queen
+            ) {
+                attackers |= bishop_attacks(to, occupancy) & diagonal;
+            }
+            if matches!(piece, PieceType::Rook | PieceType::Queen) {
+                attackers |= rook_attacks(to, occupancy) & straight;
+            }
+            attackers &= occupancy;
+            side = side.opposite();
+        }
+
+        // Each side takes back only when it pays off
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+        gains[0]
+    }
+
+    /// Returns true if [`Board::see`] of `mv` is at least `threshold`, settling most moves
+    /// without playing out the exchange
+    pub fn see_ge(&self, mv: Move, threshold: i32) -> bool {
+        let gain = self.capture_gain(mv);
+        // Recaptures can only take material back
+        if gain < threshold {
+            return false;
+        }
+        // Even losing the moved piece keeps enough
+        if gain - Self::moved_value(mv) >= threshold {
+            return true;
+        }
+        self.see(mv) >= threshold
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn find(board: &Board, name: &str) -> Move {
+        *board
+            .legal_moves()
+            .iter()
+            .find(|mv| mv.to_string() == name)
+            .unwrap_or_else(|| panic!("{} is not legal", name))
+    }
+
+    fn see(fen: &str, name: &str) -> i32 {
+        let board = Board::from_fen(fen).unwrap();
+        board.see(find(&board, name))
+    }
+
+    #[test]
+    fn test_see() {
+        // Undefended pawn
+        assert_eq!(
+            see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+            100
+        );
+        // Undefended queen
+        assert_eq!(see("4k3/8/8/3q4/8/4N3/8/4K3 w - - 0 1", "e3d5"), 900);
+        // Knight for pawn, the rest of the exchange does not pay off for white
+        assert_eq!(
+            see(
+                "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+                "d3e5"
+            ),
+            100 - 320
+        );
+        // Quiet moves to an attacked square lose the piece
+        assert_eq!(see("4k3/8/8/8/3p4/8/2N5/4K3 w - - 0 1", "c2e3"), -320);
+        assert_eq!(see("4k3/8/8/8/8/8/8/4K1N1 w - - 0 1", "g1f3"), 0);
+        assert_eq!(see("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"), 0);
+    }
+
+    #[test]
+    fn test_see_x_rays() {
+        // The rook on e1 recaptures through e2 once the first rook has gone
+        assert_eq!(see("4k3/4r3/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), 100);
+        // Without it the rook is lost for a pawn
+        assert_eq!(
+            see("4k3/4r3/8/4p3/8/8/4R3/6K1 w - - 0 1", "e2e5"),
+            100 - 500
+        );
+        // A bishop behind a pawn supports its capture
+        assert_eq!(see("4k3/8/5p2/4n3/3P4/2B5/8/4K3 w - - 0 1", "d4e5"), 320);
+        assert_eq!(
+            see("4k3/8/5p2/4n3/3P4/8/8/4K3 w - - 0 1", "d4e5"),
+            320 - 100
+        );
+    }
+
+    #[test]
+    fn test_see_king() {
+        // The king cannot take back while the queen behind the rook guards the pawn
+        assert_eq!(see("8/8/8/4k3/3p4/8/3R4/3Q2K1 w - - 0 1", "d2d4"), 100);
+        // Alone the rook is taken by the king
+        assert_eq!(see("8/8/8/4k3/3p4/8/3R4/6K1 w - - 0 1", "d2d4"), 100 - 500);
+    }
+
+    #[test]
+    fn test_see_special_moves() {
+        // En passant wins a pawn, and the capturing pawn is taken back
+        assert_eq!(see("4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 1", "d5c6"), 100);
+        assert_eq!(see("4k3/1p6/8/2pP4/8/8/8/4K3 w - c6 0 1", "d5c6"), 0);
+        // A promotion gains the difference between queen and pawn
+        assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 800);
+        assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), -100);
+    }
+
+    #[test]
+    fn test_see_ge() {
+        let board = Board::from_fen("4k3/4r3/8/4p3/8/8/4R3/6K1 w - - 0 1").unwrap();
+        let mv = find(&board, "e2e5");
+        assert!(board.see_ge(mv, -400));
+        assert!(!board.see_ge(mv, -399));
+        assert!(!board.see_ge(mv, 0));
+
+        let board = Board::from_fen("4k3/8/8/3q4/8/4N3/8/4K3 w - - 0 1").unwrap();
+        let mv = find(&board, "e3d5");
+        assert!(board.see_ge(mv, 900));
+        assert!(!board.see_ge(mv, 901));
+    }
+}
@@ -0,0 +1,846 @@
+pub mod options;
+
+use crate::ai::info::Info;
+use crate::ai::tt::Bound;
+use crate::ai::{Ai, Limits, SearchResult, Signals, MATE, MATE_BOUND};
+use crate::book::Book;
+use crate::pieces::board::{Board, Move};
+use crate::pieces::magic;
+use options::Options;
+use std::io;
+use std::str::SplitWhitespace;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const ENGINE_NAME: &str = "RustChess";
+const AUTHOR_NAME: &str = "Drake Murphy";
+
+/// Weakest strength `UCI_Elo` can be set to
+pub const MIN_ELO: u32 = 1350;
+/// Strongest strength `UCI_Elo` can be set to
+pub const MAX_ELO: u32 = 2850;
+
+/// UCI engine
+/// name: engine name
+/// author: engine author
+/// board: current position
+/// own_book: play moves from the opening book before searching
+/// book: the opening book, if one is loaded
+/// options: the options the GUI can set, with their current values
+/// limit_strength: cap the nodes of every search to play at about `elo`
+/// ponder: the GUI may ponder, so the best move comes with the move expected in reply
+/// ai: the search and its transposition table, locked by the search thread while it runs
+/// signals: stop and ponder flags of the running search
+/// search: the thread of the running search, if any
+pub struct Uci {
+    name: String,
+    author: String,
+    debug: bool,
+    board: Board,
+    own_book: bool,
+    book: Option<Book>,
+    options: Options,
+    limit_strength: bool,
+    elo: u32,
+    ponder: bool,
+    ai: Arc<Mutex<Ai>>,
+    signals: Arc<Signals>,
+    search: Option<JoinHandle<()>>,
+}
+
+impl Default for Uci {
+    fn default() -> Uci {
+        let ai = Ai::default();
+        let signals = Arc::clone(&ai.signals);
+        Uci {
+            name: ENGINE_NAME.to_string(),
+            author: AUTHOR_NAME.to_string(),
+            debug: false,
+            board: Board::default(),
+            own_book: false,
+            book: None,
+            options: Options::default(),
+            limit_strength: false,
+            elo: MIN_ELO,
+            ponder: false,
+            ai: Arc::new(Mutex::new(ai)),
+            signals,
+            search: None,
+        }
+    }
+}
+
+/// Xboard engine
+pub struct Xboard;
+
+impl Uci {
+    /// Waits for the running search, if any, to print its best move
+    fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.join().expect("search thread panicked");
+        }
+    }
+
+    /// Returns true while a search is running
+    fn searching(&self) -> bool {
+        self.search
+            .as_ref()
+            .is_some_and(|search| !search.is_finished())
+    }
+
+    /// Returns true while a search is running, after telling the GUI that `command` is
+    /// refused: waiting for the search would block the input loop, and with it the `stop`
+    /// that ends an infinite or pondering search
+    fn busy(&self, command: &str) -> bool {
+        let searching = self.searching();
+        if searching {
+            println!("info string cannot {} while searching", command);
+        }
+        searching
+    }
+
+    /// Returns the search once no search is running
+    fn ai(&mut self) -> MutexGuard<'_, Ai> {
+        self.wait();
+        self.ai.lock().unwrap()
+    }
+
+    fn debug(&mut self, params: &mut SplitWhitespace) {
+        match params.next() {
+            Some("on") => self.debug = true,
+            Some("off") => self.debug = false,
+            _ => println!("info string expected debug on or debug off"),
+        }
+    }
+
+    /// Answers right away, even while searching
+    fn is_ready(&mut self, _params: &mut SplitWhitespace) {
+        println!("readyok");
+    }
+
+    fn set_option(&mut self, params: &mut SplitWhitespace) {
+        // Both the name and the value may contain spaces
+        if params.next() != Some("name") {
+            return;
+        }
+        let name: Vec<&str> = params.take_while(|param| *param != "value").collect();
+        let value: Vec<&str> = params.collect();
+        let (name, value) = (name.join(" "), value.join(" "));
+
+        if self.busy(&format!("set {}", name)) {
+            return;
+        }
+        match self.options.set(&name, &value) {
+            Ok((on_change, value)) => on_change(self, &value),
+            Err(error) => println!("info string {}", error),
+        }
+    }
+
+    /// The engine needs no registration, so every `register` is accepted and ignored
+    fn register(&mut self, _params: &mut SplitWhitespace) {}
+
+    fn position(&mut self, params: &mut SplitWhitespace) {
+        let mut board = match params.next() {
+            Some("startpos") => {
+                // Skip the "moves" token, if any
+                params.next();
+                Board::default()
+            }
+            Some("fen") => {
+                let fen: Vec<&str> = params.take_while(|param| *param != "moves").collect();
+                match Board::from_fen(&fen.join(" ")) {
+                    Ok(board) => board,
+                    Err(error) => {
+                        println!("info string {}", error);
+                        return;
+                    }
+                }
+            }
+            _ => return,
+        };
+
+        for param in params {
+            let legal = board.legal_moves();
+            match legal.into_iter().find(|mv| mv.to_string() == param) {
+                Some(mv) => {
+                    board.make_move(mv);
+                }
+                None => {
+                    println!("info string illegal move {}", param);
+                    break;
+                }
+            }
+        }
+        self.board = board;
+    }
+
+    /// Starts a search on a new thread, which prints the best move when it is done
+    fn go(&mut self, params: &mut SplitWhitespace) {
+        if self.busy("go") {
+            return;
+        }
+        self.wait();
+        if params.clone().next() == Some("perft") {
+            params.next();
+            self.divide(params);
+            return;
+        }
+
+        let mut limits = self.parse_go(params);
+        // A book move comes at once, which an infinite or pondering search must not do, and
+        // knows nothing of searchmoves
+        if !limits.infinite && !limits.ponder && limits.search_moves.is_empty() {
+            if let Some(mv) = self.book_move() {
+                println!("bestmove {}", mv);
+                return;
+            }
+        }
+        if self.limit_strength {
+            let nodes = Self::strength_nodes(self.elo);
+            limits.nodes = Some(limits.nodes.map_or(nodes, |limit| limit.min(nodes)));
+        }
+        // Set before the thread starts, so a quick stop or ponderhit is not overwritten
+        self.signals.stop.store(false, Ordering::Relaxed);
+        self.signals.ponder.store(limits.ponder, Ordering::Relaxed);
+
+        let ai = Arc::clone(&self.ai);
+        let mut board = self.board.clone();
+        let debug = self.debug;
+        let ponder = self.ponder;
+        self.search = Some(thread::spawn(move || {
+            let mut ai = ai.lock().unwrap();
+            ai.info = Some(Box::new(move |info| {
+                if let Some(line) = Self::format_info(info, debug) {
+                    println!("{}", line);
+                }
+            }));
+            let result = ai.search(&mut board, limits);
+            ai.info = None;
+            Self::print_result(&result, ponder);
+        }));
+    }
+
+    /// Returns the nodes a search may take to play at about `elo`, doubling every 125 points
+    fn strength_nodes(elo: u32) -> u64 {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+        (100.0 * 2f64.powf((elo - 1000) as f64 / 125.0)) as u64
+    }
+
+    /// Returns the `info` line reporting the progress of a search, or None for diagnostics
+    /// while debug mode is off
+    fn format_info(info: &Info, debug: bool) -> Option<String> {
+        match info {
+            Info::Iteration(info) => {
+                let bound = match info.bound {
+                    Bound::Lower => " lowerbound",
+                    Bound::Upper => " upperbound",
+                    _ => "",
+                };
+                let pv: Vec<String> = info.pv.iter().map(|mv| mv.to_string()).collect();
+                Some(format!(
+                    "info depth {} seldepth {} multipv {} score {}{} nodes {} nps {} hashfull {} tbhits {} time {} pv {}",
+                    info.depth,
+                    info.seldepth,
+                    info.multipv,
+                    Self::format_score(info.score),
+                    bound,
+                    info.nodes,
+                    info.nps(),
+                    info.hashfull,
+                    info.tbhits,
+                    info.time.as_millis(),
+                    pv.join(" ")
+                ))
+            }
+            Info::CurrMove { depth, mv, number } => Some(format!(
+                "info depth {} currmove {} currmovenumber {}",
+                depth, mv, number
+            )),
+            Info::String(text) if debug => Some(format!("info string {}", text)),
+            Info::String(_) => None,
+        }
+    }
+
+    /// Returns a score as `cp` or, for a forced mate, `mate` in moves, negative when getting
+    /// mated
+    fn format_score(score: i32) -> String {
+        if score.abs() >= MATE_BOUND {
+            let moves = (MATE - score.abs() + 1) / 2;
+            format!("mate {}", score.signum() * moves)
+        } else {
+            format!("cp {}", score)
+        }
+    }
+
+    /// Prints the best move of a search, with the move expected in reply to ponder on if
+    /// `ponder` is set and one is known
+    fn print_result(result: &SearchResult, ponder: bool) {
+        println!("{}", Self::best_move(result, ponder));
+    }
+
+    /// Returns the `bestmove` line of a search, the null move `0000` when the side to move
+    /// has no legal move
+    fn best_move(result: &SearchResult, ponder: bool) -> String {
+        if result.best_move == Move::NULL {
+            "bestmove 0000".to_string()
+        } else if ponder && result.ponder_move != Move::NULL {
+            format!(
+                "bestmove {} ponder {}",
+                result.best_move, result.ponder_move
+            )
+        } else {
+            format!("bestmove {}", result.best_move)
+        }
+    }
+
+    /// Reads the search limits of a `go` command
+    fn parse_go(&self, params: &mut SplitWhitespace) -> Limits {
+        let mut limits = Limits::default();
+        while let Some(command) = params.next() {
+            match command {
+                "infinite" => limits.infinite = true,
+                "ponder" => limits.ponder = true,
+                "searchmoves" => {
+                    // Every following token that is a legal move
+                    let legal = self.board.legal_moves();
+                    while let Some(mv) = params
+                        .clone()
+                        .next()
+                        .and_then(|name| legal.iter().find(|mv| mv.to_string() == name))
+                    {
+                        limits.search_moves.push(*mv);
+                        params.next();
+                    }
+                }
+                _ => {
+                    let Some(value) = params.next().and_then(|value| value.parse::<u64>().ok())
+                    else {
+                        println!("info string missing value for go parameter {}", command);
+                        continue;
+                    };
+                    let millis = Some(Duration::from_millis(value));
+                    match command {
+                        "wtime" => limits.wtime = millis,
+                        "btime" => limits.btime = millis,
+                        "winc" => limits.winc = millis,
+                        "binc" => limits.binc = millis,
+                        "movestogo" => limits.movestogo = Some(value as u32),
+                        "movetime" => limits.movetime = millis,
+                        "depth" => limits.depth = Some(value as usize),
+                        "nodes" => limits.nodes = Some(value),
+                        "mate" => limits.mate = Some(value as usize),
+                        _ => println!("info string unsupported go parameter {}", command),
+                    }
+                }
+            }
+        }
+        limits
+    }
+
+    /// Returns a move from the opening book when `OwnBook` is on
+    fn book_move(&mut self) -> Option<Move> {
+        if !self.own_book {
+            return None;
+        }
+        match self.book.as_mut()?.pick(&self.board) {
+            Ok(mv) => mv,
+            Err(error) => {
+                println!("info string book error: {}", error);
+                None
+            }
+        }
+    }
+
+    /// Ends the running search, which still prints its best move
+    fn stop(&mut self, _params: &mut SplitWhitespace) {
+        self.signals.stop.store(true, Ordering::Relaxed);
+        self.wait();
+    }
+
+    /// The opponent played the expected move: the pondering search goes on as a normal one
+    fn ponder_hit(&mut self, _params: &mut SplitWhitespace) {
+        self.signals.ponder.store(false, Ordering::Relaxed);
+    }
+
+    /// Stops the search before the engine exits
+    fn quit(&mut self, params: &mut SplitWhitespace) {
+        self.stop(params);
+    }
+
+    /// Forgets everything learned in the previous game
+    fn uci_new_game(&mut self, _params: &mut SplitWhitespace) {
+        if self.busy("ucinewgame") {
+            return;
+        }
+        let mut ai = self.ai();
+        ai.tt.clear();
+        ai.data.clear();
+    }
+
+    /// Parses the depth argument of `perft` and `divide`
+    fn perft_depth(params: &mut SplitWhitespace) -> Option<usize> {
+        match params.next().map(str::parse) {
+            Some(Ok(depth)) if depth > 0 => Some(depth),
+            _ => {
+                println!("info string expected a depth of at least 1");
+                None
+            }
+        }
+    }
+
+    /// Prints the node total, elapsed time and speed after a perft run
+    fn print_perft_totals(nodes: u64, elapsed: Duration) {
+        let nps = nodes as u128 * 1_000_000 / elapsed.as_micros().max(1);
+        println!("Nodes searched: {}", nodes);
+        println!("Time (ms): {}", elapsed.as_millis());
+        println!("Nodes/second: {}", nps);
+    }
+
+    /// `perft <depth>`, `divide <depth>` and `go perft <depth>`: prints the leaf nodes below
+    /// each move like Stockfish's `go perft`, followed by the totals
+    ///
+    /// The three names do the same on purpose: the breakdown is what gets diffed against
+    /// other engines, and each of them knows the command under a different name.
+    fn divide(&mut self, params: &mut SplitWhitespace) {
+        if self.busy("perft") {
+            return;
+        }
+        if let Some(depth) = Self::perft_depth(params) {
+            let start = Instant::now();
+            let mut nodes = 0;
+            for (mv, count) in self.board.divide(depth) {
+                println!("{}: {}", mv, count);
+                nodes += count;
+            }
+            println!();
+            Self::print_perft_totals(nodes, start.elapsed());
+        }
+    }
+
+    fn engine_loop(&mut self, lines: &mut impl Iterator<Item = io::Result<String>>) {
+        println!("id name {}", self.name);
+        println!("id author {}", self.author);
+        for option in self.options.iter() {
+            println!("{}", option);
+        }
+        println!("uciok");
+        for line in lines {
+            let line = line.unwrap();
+            let mut params = line.split_whitespace();
+
+            if let Some(command) = params.next() {
+                match command {
+                    "debug" => self.debug(&mut params),
+                    "isready" => self.is_ready(&mut params),
+                    "setoption" => self.set_option(&mut params),
+                    "register" => self.register(&mut params),
+                    "position" => self.position(&mut params),
+                    "go" => self.go(&mut params),
+                    "stop" => self.stop(&mut params),
+                    "ponderhit" => self.ponder_hit(&mut params),
+                    "quit" => {
+                        self.quit(&mut params);
+                        return;
+                    }
+                    "ucinewgame" => self.uci_new_game(&mut params),
+                    // Aliases, see `divide`
+                    "perft" | "divide" => self.divide(&mut params),
+                    _ => println!("Unkown command: {}", command),
+                }
+            }
+        }
+        // Input ended without quit
+        self.quit(&mut "".split_whitespace());
+    }
+}
+
+/// Main entry point
+pub fn entry() {
+    magic::init();
+    let mut lines = io::stdin().lines();
+    while let Some(line) = lines.next() {
+        let line = line.unwrap();
+        let mut params = line.split_whitespace();
+
+        if let Some(command) = params.next() {
+            match command {
+                "uci" => {
+                    let mut engine = Uci::default();
+                    engine.engine_loop(&mut lines);
+                    return;
+                }
+                _ => println!("Unkown command: {}", command),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ai::info::IterationInfo;
+
+    fn position(uci: &mut Uci, command: &str) {
+        uci.position(&mut command.split_whitespace());
+    }
+
+    #[test]
+    fn test_position() {
+        let mut uci = Uci::default();
+        position(&mut uci, "startpos moves e2e4 c7c5 g1f3");
+        assert_eq!(
+            uci.board.to_fen(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+
+        position(&mut uci, "fen 4k3/8/8/8/8/8/8/4K2R w K - 0 1 moves e1g1");
+        assert_eq!(uci.board.to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+
+        // Moves after an illegal one are dropped
+        position(&mut uci, "startpos moves e2e5 e2e4");
+        assert_eq!(uci.board, Board::default());
+    }
+
+    fn go(uci: &Uci, command: &str) -> Limits {
+        uci.parse_go(&mut command.split_whitespace())
+    }
+
+    #[test]
+    fn test_parse_go() {
+        let mut uci = Uci::default();
+        let limits = go(
+            &uci,
+            "wtime 60000 btime 59000 winc 1000 binc 900 movestogo 20 depth 12 nodes 5000 mate 3",
+        );
+        assert_eq!(limits.wtime, Some(Duration::from_millis(60_000)));
+        assert_eq!(limits.btime, Some(Duration::from_millis(59_000)));
+        assert_eq!(limits.winc, Some(Duration::from_millis(1000)));
+        assert_eq!(limits.binc, Some(Duration::from_millis(900)));
+        assert_eq!(limits.movestogo, Some(20));
+        assert_eq!(
+            (limits.depth, limits.nodes, limits.mate),
+            (Some(12), Some(5000), Some(3))
+        );
+        assert!(!limits.infinite && !limits.ponder);
+
+        let limits = go(&uci, "ponder movetime 500 infinite");
+        assert_eq!(limits.movetime, Some(Duration::from_millis(500)));
+        assert!(limits.infinite && limits.ponder);
+
+        // Search moves end at the first token that is not a legal move
+        position(&mut uci, "startpos");
+        let limits = go(&uci, "searchmoves e2e4 d2d4 depth 2");
+        let names: Vec<String> = limits
+            .search_moves
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect();
+        assert_eq!(names, ["e2e4", "d2d4"]);
+        assert_eq!(limits.depth, Some(2));
+    }
+
+    #[test]
+    fn test_format_info() {
+        let mv = Board::default().legal_moves()[0];
+        let mut info = IterationInfo {
+            depth: 5,
+            seldepth: 9,
+            multipv: 1,
+            score: 35,
+            bound: Bound::Exact,
+            nodes: 20_000,
+            time: Duration::from_millis(100),
+            hashfull: 12,
+            tbhits: 0,
+            pv: vec![mv],
+        };
+        assert_eq!(
+            Uci::format_info(&Info::Iteration(info.clone()), false).unwrap(),
+            "info depth 5 seldepth 9 multipv 1 score cp 35 nodes 20000 nps 200000 hashfull 12 tbhits 0 time 100 pv b1a3"
+        );
+        info.score = MATE - 3;
+        info.bound = Bound::Lower;
+        let line = Uci::format_info(&Info::Iteration(info), false).unwrap();
+        assert!(line.contains(" score mate 2 lowerbound nodes "));
+        assert_eq!(Uci::format_score(-MATE + 2), "mate -1");
+
+        let currmove = Info::CurrMove {
+            depth: 7,
+            mv,
+            number: 3,
+        };
+        assert_eq!(
+            Uci::format_info(&currmove, false).unwrap(),
+            "info depth 7 currmove b1a3 currmovenumber 3"
+        );
+
+        // Diagnostics only in debug mode
+        let text = Info::String("hello".to_string());
+        assert_eq!(Uci::format_info(&text, false), None);
+        assert_eq!(Uci::format_info(&text, true).unwrap(), "info string hello");
+    }
+
+    #[test]
+    fn test_move_overhead_option() {
+        let mut uci = Uci::default();
+        uci.set_option(&mut "name Move Overhead value 250".split_whitespace());
+        assert_eq!(uci.ai().params.move_overhead, Duration::from_millis(250));
+        uci.set_option(&mut "name Move Overhead value 99999".split_whitespace());
+        assert_eq!(uci.ai().params.move_overhead, Duration::from_millis(250));
+    }
+
+    fn send(uci: &mut Uci, line: &str) {
+        let lines = [line, "quit"].map(|line| Ok(line.to_string()));
+        uci.engine_loop(&mut lines.into_iter());
+    }
+
+    #[test]
+    fn test_go_and_stop() {
+        let mut uci = Uci::default();
+        position(&mut uci, "startpos");
+
+        // An infinite search only ends when told to
+        uci.go(&mut "infinite".split_whitespace());
+        thread::sleep(Duration::from_millis(50));
+        assert!(uci
+            .search
+            .as_ref()
+            .is_some_and(|search| !search.is_finished()));
+        uci.stop(&mut "".split_whitespace());
+        assert!(uci.search.is_none());
+
+        // A pondering search waits for ponderhit even after reaching its depth
+        uci.go(&mut "ponder depth 1".split_whitespace());
+        thread::sleep(Duration::from_millis(50));
+        assert!(uci
+            .search
+            .as_ref()
+            .is_some_and(|search| !search.is_finished()));
+        uci.ponder_hit(&mut "".split_whitespace());
+        uci.wait();
+
+        // A limited search ends by itself, and a command that has to wait for it is refused
+        // until then
+        uci.go(&mut "depth 20".split_whitespace());
+        uci.uci_new_game(&mut "".split_whitespace());
+        assert!(uci.searching());
+        uci.stop(&mut "".split_whitespace());
+        uci.uci_new_game(&mut "".split_whitespace());
+        assert_eq!(uci.ai().tt.hashfull(), 0);
+    }
+
+    #[test]
+    fn test_ponder_option() {
+        let mut uci = Uci::default();
+        let mut board = Board::default();
+        let result = uci.ai().search(
+            &mut board,
+            Limits {
+                depth: Some(3),
+                ..Limits::default()
+            },
+        );
+        let best_move = format!("bestmove {}", result.best_move);
+        assert_eq!(Uci::best_move(&result, uci.ponder), best_move);
+
+        uci.set_option(&mut "name Ponder value true".split_whitespace());
+        assert!(uci.ponder);
+        assert_eq!(
+            Uci::best_move(&result, uci.ponder),
+            format!("{} ponder {}", best_move, result.ponder_move)
+        );
+        let no_reply = SearchResult {
+            ponder_move: Move::NULL,
+            ..result
+        };
+        assert_eq!(Uci::best_move(&no_reply, uci.ponder), best_move);
+    }
+
+    #[test]
+    fn test_no_legal_moves() {
+        let mut uci = Uci::default();
+        uci.set_option(&mut "name Ponder value true".split_whitespace());
+        for fen in [
+            "R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+            "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+        ] {
+            let mut board = Board::from_fen(fen).unwrap();
+            let result = uci.ai().search(
+                &mut board,
+                Limits {
+                    depth: Some(3),
+                    ..Limits::default()
+                },
+            );
+            assert_eq!(Uci::best_move(&result, uci.ponder), "bestmove 0000");
+        }
+    }
+
+    #[test]
+    fn test_unknown_commands() {
+        let mut uci = Uci::default();
+        let lines = ["test x", "foo bar", "position startpos moves e2e4", "quit"];
+        uci.engine_loop(&mut lines.map(|line| Ok(line.to_string())).into_iter());
+        // The loop went on to the commands after them
+        assert_eq!(
+            uci.board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+    }
+
+    #[test]
+    fn test_commands_while_searching() {
+        let mut uci = Uci::default();
+        let lines = [
+            "go infinite",
+            "ucinewgame",
+            "go depth 1",
+            "perft 3",
+            "go perft 3",
+            "isready",
+            "stop",
+            "position startpos moves e2e4",
+            "quit",
+        ];
+        // Every command is answered at once, so the loop reads the stop and gets to the end
+        uci.engine_loop(&mut lines.map(|line| Ok(line.to_string())).into_iter());
+        assert_eq!(
+            uci.board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        assert!(uci.search.is_none());
+    }
+
+    #[test]
+    fn test_quit_stops_search() {
+        let mut uci = Uci::default();
+        send(&mut uci, "go infinite");
+        assert!(uci.search.is_none());
+        assert!(uci.signals.stop.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_book_options() {
+        let mut uci = Uci::default();
+        uci.set_option(&mut "name OwnBook value true".split_whitespace());
+        assert!(uci.own_book);
+        // Without a book there is nothing to play
+        assert_eq!(uci.book_move(), None);
+
+        uci.set_option(&mut "name BookFile value /nonexistent/book.bin".split_whitespace());
+        assert!(uci.book.is_none());
+        uci.set_option(&mut "name ownbook value false".split_whitespace());
+        assert!(!uci.own_book);
+    }
+
+    #[test]
+    fn test_book_move_limits() {
+        use crate::book::polyglot_key;
+        use std::io::Write;
+
+        // A book that only knows e2e4 from the start position
+        let path = std::env::temp_dir().join("rust_chess_test_book_move_limits.bin");
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(&polyglot_key(&Board::default()).to_be_bytes())
+            .unwrap();
+        file.write_all(&((12u16 << 6) | 28).to_be_bytes()).unwrap();
+        file.write_all(&[0, 1, 0, 0, 0, 0]).unwrap();
+        drop(file);
+
+        let mut uci = Uci::default();
+        uci.set_option(&mut "name OwnBook value true".split_whitespace());
+        let command = format!("name BookFile value {}", path.display());
+        uci.set_option(&mut command.split_whitespace());
+        assert!(uci.book.is_some());
+
+        // Played at once without starting a search
+        uci.go(&mut "depth 1".split_whitespace());
+        assert!(uci.search.is_none());
+
+        // These searches have to run
+        for command in ["infinite", "ponder depth 1", "searchmoves d2d4 depth 1"] {
+            uci.go(&mut command.split_whitespace());
+            assert!(uci.search.is_some(), "{}", command);
+            uci.stop(&mut "".split_whitespace());
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_options_while_searching() {
+        let mut uci = Uci::default();
+        uci.go(&mut "infinite".split_whitespace());
+        uci.set_option(&mut "name MultiPV value 2".split_whitespace());
+        uci.set_option(&mut "name Hash value 1".split_whitespace());
+        assert!(uci.searching());
+        assert_eq!(uci.options.get("MultiPV").unwrap().value().spin(), 1);
+        uci.stop(&mut "".split_whitespace());
+
+        uci.set_option(&mut "name MultiPV value 2".split_whitespace());
+        assert_eq!(uci.ai().params.multi_pv, 2);
+    }
+
+    #[test]
+    fn test_hash_option() {
+        let mut uci = Uci::default();
+        let default = uci.ai().tt.len();
+        uci.set_option(&mut "name Hash value 1".split_whitespace());
+        assert_eq!(uci.ai().tt.len() * 16, default);
+        uci.set_option(&mut "name Hash value 0".split_whitespace());
+        assert_eq!(uci.ai().tt.len() * 16, default);
+    }
+
+    #[test]
+    fn test_aspiration_option() {
+        let mut uci = Uci::default();
+        uci.set_option(&mut "name AspirationWindow value 0".split_whitespace());
+        assert_eq!(uci.ai().params.aspiration_window, 0);
+        uci.set_option(&mut "name AspirationWindow value -5".split_whitespace());
+        assert_eq!(uci.ai().params.aspiration_window, 0);
+    }
+
+    #[test]
+    fn test_strength_options() {
+        let mut uci = Uci::default();
+        uci.set_option(&mut "name UCI_LimitStrength value true".split_whitespace());
+        uci.set_option(&mut "name UCI_Elo value 1500".split_whitespace());
+        assert!(uci.limit_strength);
+        assert_eq!(uci.elo, 1500);
+        // Out of range
+        uci.set_option(&mut "name UCI_Elo value 4000".split_whitespace());
+        assert_eq!(uci.elo, 1500);
+
+        assert!(Uci::strength_nodes(MIN_ELO) < Uci::strength_nodes(1500));
+        assert_eq!(Uci::strength_nodes(1625), 2 * Uci::strength_nodes(1500));
+        assert_eq!(Uci::strength_nodes(9999), Uci::strength_nodes(MAX_ELO));
+    }
+
+    #[test]
+    fn test_clear_hash_option() {
+        let mut uci = Uci::default();
+        position(&mut uci, "startpos");
+        uci.go(&mut "depth 4".split_whitespace());
+        assert!(uci.ai().tt.hashfull() > 0);
+        uci.set_option(&mut "name Clear Hash".split_whitespace());
+        assert_eq!(uci.ai().tt.hashfull(), 0);
+    }
+
+    #[test]
+    fn test_pruning_options() {
+        let mut uci = Uci::default();
+        for name in ["NullMove", "LMR", "ReverseFutility", "Futility"] {
+            let command = format!("name {} value false", name);
+            uci.set_option(&mut command.split_whitespace());
+        }
+        let params = uci.ai().params.clone();
+        assert!(!params.null_move && !params.late_move_reductions);
+        assert!(!params.reverse_futility && !params.futility);
+
+        uci.set_option(&mut "name lmr value true".split_whitespace());
+        assert!(uci.ai().params.late_move_reductions);
+        uci.set_option(&mut "name RecaptureExtension value true".split_whitespace());
+        assert!(uci.ai().params.recapture_extension);
+    }
+}
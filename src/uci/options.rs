@@ -0,0 +1,397 @@
+use super::{Uci, MAX_ELO, MIN_ELO};
+use crate::ai::time::{DEFAULT_MOVE_OVERHEAD, MAX_MOVE_OVERHEAD};
+use crate::ai::tt::{DEFAULT_HASH_MB, MAX_HASH_MB};
+use crate::ai::{DEFAULT_ASPIRATION_WINDOW, MAX_ASPIRATION_WINDOW, MAX_MULTI_PV};
+use crate::book::Book;
+use std::fmt;
+use std::time::Duration;
+
+/// How the GUI shows a string option that is not set
+const EMPTY: &str = "<empty>";
+
+/// Type of an option with its default and the values it accepts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionType {
+    Check {
+        default: bool,
+    },
+    Spin {
+        default: i64,
+        min: i64,
+        max: i64,
+    },
+    Combo {
+        default: &'static str,
+        vars: &'static [&'static str],
+    },
+    /// Runs its callback when set and has no value
+    Button,
+    String {
+        default: &'static str,
+    },
+}
+
+/// Value of an option, matching its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    Check(bool),
+    Spin(i64),
+    Combo(String),
+    Button,
+    /// Empty when the GUI sends `<empty>`
+    String(String),
+}
+
+impl OptionValue {
+    /// Returns the value of a check option, false for any other type
+    pub fn check(&self) -> bool {
+        matches!(self, OptionValue::Check(true))
+    }
+
+    /// Returns the value of a spin option, 0 for any other type
+    pub fn spin(&self) -> i64 {
+        match self {
+            OptionValue::Spin(value) => *value,
+            _ => 0,
+        }
+    }
+
+    /// Returns the value of a combo or string option, empty for any other type
+    pub fn string(&self) -> &str {
+        match self {
+            OptionValue::Combo(value) | OptionValue::String(value) => value,
+            _ => "",
+        }
+    }
+}
+
+/// Called with the new value after `setoption` changed an option
+pub type OnChange = fn(&mut Uci, &OptionValue);
+
+/// An option the GUI can set
+/// name: matched without regard to case
+/// kind: type, default and the values accepted
+/// value: current value
+/// on_change: applies a new value to the engine
+#[derive(Clone)]
+pub struct UciOption {
+    name: &'static str,
+    kind: OptionType,
+    value: OptionValue,
+    on_change: OnChange,
+}
+
+impl UciOption {
+    pub fn new(name: &'static str, kind: OptionType, on_change: OnChange) -> Self {
+        let value = match &kind {
+            OptionType::Check { default } => OptionValue::Check(*default),
+            OptionType::Spin { default, .. } => OptionValue::Spin(*default),
+            OptionType::Combo { default, .. } => OptionValue::Combo(default.to_string()),
+            OptionType::Button => OptionValue::Button,
+            OptionType::String { default } => OptionValue::String(default.to_string()),
+        };
+        UciOption {
+            name,
+            kind,
+            value,
+            on_change,
+        }
+    }
+
+    /// Returns the name
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Returns the current value
+    pub fn value(&self) -> &OptionValue {
+        &self.value
+    }
+
+    /// Checks `value` against the type of the option
+    pub fn parse(&self, value: &str) -> Result<OptionValue, String> {
+        let invalid = || format!("invalid {} value {}", self.name, value);
+        match &self.kind {
+            OptionType::Check { .. } => match value.to_ascii_lowercase().as_str() {
+                "true" => Ok(OptionValue::Check(true)),
+                "false" => Ok(OptionValue::Check(false)),
+                _ => Err(invalid()),
+            },
+            OptionType::Spin { min, max, .. } => match value.parse::<i64>() {
+                Ok(value) if (*min..=*max).contains(&value) => Ok(OptionValue::Spin(value)),
+                _ => Err(invalid()),
+            },
+            OptionType::Combo { vars, .. } => vars
+                .iter()
+                .find(|var| var.eq_ignore_ascii_case(value))
+                .map(|var| OptionValue::Combo(var.to_string()))
+                .ok_or_else(invalid),
+            OptionType::Button => Ok(OptionValue::Button),
+            OptionType::String { .. } if value == EMPTY => Ok(OptionValue::String(String::new())),
+            OptionType::String { .. } => Ok(OptionValue::String(value.to_string())),
+        }
+    }
+}
+
+/// Prints the option as the `option` line of the `uci` handshake
+impl fmt::Display for UciOption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+        match &self.kind {
+            OptionType::Check { default } => write!(f, "check default {}", default),
+            OptionType::Spin { default, min, max } => {
+                write!(f, "spin default {} min {} max {}", default, min, max)
+            }
+            OptionType::Combo { default, vars } => {
+                write!(f, "combo default {}", default)?;
+                vars.iter().try_for_each(|var| write!(f, " var {}", var))
+            }
+            OptionType::Button => write!(f, "button"),
+            OptionType::String { default: "" } => write!(f, "string default {}", EMPTY),
+            OptionType::String { default } => write!(f, "string default {}", default),
+        }
+    }
+}
+
+/// Every option of the engine, in the order the handshake lists them
+#[derive(Clone)]
+pub struct Options(Vec<UciOption>);
+
+impl Options {
+    pub fn new(options: Vec<UciOption>) -> Self {
+        Options(options)
+    }
+
+    /// Returns the option called `name`, whatever its case
+    pub fn get(&self, name: &str) -> Option<&UciOption> {
+        self.0
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Sets the option called `name` to `value`, returning the callback to apply it with and
+    /// the new value
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(OnChange, OptionValue), String> {
+        let option = self
+            .0
+            .iter_mut()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown option {}", name))?;
+        option.value = option.parse(value)?;
+        Ok((option.on_change, option.value.clone()))
+    }
+
+    /// Returns the options in handshake order
+    pub fn iter(&self) -> impl Iterator<Item = &UciOption> {
+        self.0.iter()
+    }
+}
+
+impl Default for Options {
+    /// The options of the engine, each defaulting to the state a new [`Uci`] starts in
+    fn default() -> Self {
+        use OptionType::*;
+
+        Options::new(vec![
+            UciOption::new(
+                "Hash",
+                Spin {
+                    default: DEFAULT_HASH_MB as i64,
+                    min: 1,
+                    max: MAX_HASH_MB as i64,
+                },
+                |uci, value| uci.ai().tt.resize(value.spin() as usize),
+            ),
+            // The search runs on a single thread
+            UciOption::new(
+                "Threads",
+                Spin {
+                    default: 1,
+                    min: 1,
+                    max: 1,
+                },
+                |_, _| {},
+            ),
+            UciOption::new(
+                "MultiPV",
+                Spin {
+                    default: 1,
+                    min: 1,
+                    max: MAX_MULTI_PV as i64,
+                },
+                |uci, value| uci.ai().params.multi_pv = value.spin() as usize,
+            ),
+            UciOption::new("Ponder", Check { default: false }, |uci, value| {
+                uci.ponder = value.check()
+            }),
+            UciOption::new("OwnBook", Check { default: false }, |uci, value| {
+                uci.own_book = value.check()
+            }),
+            UciOption::new("BookFile", String { default: "" }, |uci, value| {
+                uci.book = None;
+                let path = value.string();
+                if !path.is_empty() {
+                    match Book::open(path) {
+                        Ok(book) => uci.book = Some(book),
+                        Err(error) => println!("info string cannot open book {}: {}", path, error),
+                    }
+                }
+            }),
+            UciOption::new("SyzygyPath", String { default: "" }, |_, value| {
+                if !value.string().is_empty() {
+                    println!("info string tablebases are not supported");
+                }
+            }),
+            UciOption::new(
+                "Move Overhead",
+                Spin {
+                    default: DEFAULT_MOVE_OVERHEAD.as_millis() as i64,
+                    min: 0,
+                    max: MAX_MOVE_OVERHEAD.as_millis() as i64,
+                },
+                |uci, value| {
+                    uci.ai().params.move_overhead = Duration::from_millis(value.spin() as u64)
+                },
+            ),
+            UciOption::new("UCI_Chess960", Check { default: false }, |_, value| {
+                if value.check() {
+                    println!("info string Chess960 castling is not supported");
+                }
+            }),
+            UciOption::new(
+                "UCI_LimitStrength",
+                Check { default: false },
+                |uci, value| uci.limit_strength = value.check(),
+            ),
+            UciOption::new(
+                "UCI_Elo",
+                Spin {
+                    default: MIN_ELO as i64,
+                    min: MIN_ELO as i64,
+                    max: MAX_ELO as i64,
+                },
+                |uci, value| uci.elo = value.spin() as u32,
+            ),
+            UciOption::new("Clear Hash", Button, |uci, _| {
+                let mut ai = uci.ai();
+                ai.tt.clear();
+                ai.data.clear();
+            }),
+            UciOption::new(
+                "AspirationWindow",
+                Spin {
+                    default: DEFAULT_ASPIRATION_WINDOW as i64,
+                    min: 0,
+                    max: MAX_ASPIRATION_WINDOW as i64,
+                },
+                |uci, value| uci.ai().params.aspiration_window = value.spin() as i32,
+            ),
+            UciOption::new("NullMove", Check { default: true }, |uci, value| {
+                uci.ai().params.null_move = value.check()
+            }),
+            UciOption::new("LMR", Check { default: true }, |uci, value| {
+                uci.ai().params.late_move_reductions = value.check()
+            }),
+            UciOption::new("ReverseFutility", Check { default: true }, |uci, value| {
+                uci.ai().params.reverse_futility = value.check()
+            }),
+            UciOption::new("Futility", Check { default: true }, |uci, value| {
+                uci.ai().params.futility = value.check()
+            }),
+            UciOption::new(
+                "RecaptureExtension",
+                Check { default: false },
+                |uci, value| uci.ai().params.recapture_extension = value.check(),
+            ),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let check = UciOption::new("Check", OptionType::Check { default: false }, |_, _| {});
+        assert_eq!(check.parse("TRUE"), Ok(OptionValue::Check(true)));
+        assert!(check.parse("yes").is_err());
+
+        let spin = UciOption::new(
+            "Spin",
+            OptionType::Spin {
+                default: 5,
+                min: -2,
+                max: 10,
+            },
+            |_, _| {},
+        );
+        assert_eq!(spin.value().spin(), 5);
+        assert_eq!(spin.parse("-2"), Ok(OptionValue::Spin(-2)));
+        assert!(spin.parse("11").is_err() && spin.parse("1.5").is_err());
+
+        let combo = UciOption::new(
+            "Style",
+            OptionType::Combo {
+                default: "Normal",
+                vars: &["Solid", "Normal", "Risky"],
+            },
+            |_, _| {},
+        );
+        assert_eq!(
+            combo.parse("risky"),
+            Ok(OptionValue::Combo("Risky".to_string()))
+        );
+        assert!(combo.parse("Wild").is_err());
+
+        let string = UciOption::new("Path", OptionType::String { default: "" }, |_, _| {});
+        assert_eq!(string.parse("<empty>").unwrap().string(), "");
+        assert_eq!(string.parse("a b").unwrap().string(), "a b");
+    }
+
+    #[test]
+    fn test_display() {
+        let options = Options::default();
+        let lines: Vec<String> = options.iter().map(|option| option.to_string()).collect();
+        assert_eq!(
+            lines[0],
+            format!(
+                "option name Hash type spin default {} min 1 max {}",
+                DEFAULT_HASH_MB, MAX_HASH_MB
+            )
+        );
+        assert!(lines.contains(&"option name Ponder type check default false".to_string()));
+        assert!(lines.contains(&"option name SyzygyPath type string default <empty>".to_string()));
+        assert!(lines.contains(&"option name Clear Hash type button".to_string()));
+
+        let combo = UciOption::new(
+            "Style",
+            OptionType::Combo {
+                default: "Normal",
+                vars: &["Solid", "Normal"],
+            },
+            |_, _| {},
+        );
+        assert_eq!(
+            combo.to_string(),
+            "option name Style type combo default Normal var Solid var Normal"
+        );
+    }
+
+    #[test]
+    fn test_set() {
+        let mut options = Options::default();
+        assert!(options.set("multipv", "3").is_ok());
+        assert_eq!(
+            options.get("MultiPV").unwrap().value(),
+            &OptionValue::Spin(3)
+        );
+        // An invalid value keeps the old one
+        assert!(options.set("MultiPV", "0").is_err());
+        assert_eq!(options.get("MultiPV").unwrap().value().spin(), 3);
+        assert_eq!(
+            options.set("Contempt", "10").err().unwrap(),
+            "unknown option Contempt"
+        );
+    }
+}
@@ -1,18 +1,53 @@
-use rand::Rng;
+use crate::pieces::board::{Board, PieceType, Side, Square};
+use crate::Castling;
 
+/// Seed of the keys used by every [`Board`] hash
+pub const DEFAULT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Keys used by every [`Board`] hash, generated at compile time
+pub static KEYS: Zobrist = Zobrist::new(DEFAULT_SEED);
+
+/// SplitMix64 generator, simple enough to run in a `const fn`
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    const fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns one key per square
+    const fn table(&mut self) -> [u64; 64] {
+        let mut table = [0; 64];
+        let mut square = 0;
+        while square < 64 {
+            table[square] = self.next();
+            square += 1;
+        }
+        table
+    }
+}
+
+/// Pseudo-random keys for each feature of a position, xored together into a position hash
+///
+/// The keys only depend on the seed, so hashes are the same on every run and machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Zobrist {
-    pawn_white: Vec<u64>,
-    knight_white: Vec<u64>,
-    bishop_white: Vec<u64>,
-    rook_white: Vec<u64>,
-    queen_white: Vec<u64>,
-    king_white: Vec<u64>,
-    pawn_black: Vec<u64>,
-    knight_black: Vec<u64>,
-    bishop_black: Vec<u64>,
-    rook_black: Vec<u64>,
-    queen_black: Vec<u64>,
-    king_black: Vec<u64>,
+    pawn_white: [u64; 64],
+    knight_white: [u64; 64],
+    bishop_white: [u64; 64],
+    rook_white: [u64; 64],
+    queen_white: [u64; 64],
+    king_white: [u64; 64],
+    pawn_black: [u64; 64],
+    knight_black: [u64; 64],
+    bishop_black: [u64; 64],
+    rook_black: [u64; 64],
+    queen_black: [u64; 64],
+    king_black: [u64; 64],
     move_black: u64,
     castling_king_black: u64,
     castling_queen_black: u64,
@@ -30,36 +65,156 @@ pub struct Zobrist {
 
 impl Default for Zobrist {
     fn default() -> Self {
-        // Get a random number generator
-        let mut rng = rand::thread_rng();
+        Zobrist::new(DEFAULT_SEED)
+    }
+}
 
-        // Generate the zobrist
+impl Zobrist {
+    /// Generates every key from `seed`
+    ///
+    /// Example
+    /// ```
+    /// use rust_chess::zobrist::{Zobrist, DEFAULT_SEED, KEYS};
+    ///
+    /// assert_eq!(Zobrist::new(DEFAULT_SEED), KEYS);
+    /// assert_ne!(Zobrist::new(1), KEYS);
+    /// ```
+    pub const fn new(seed: u64) -> Self {
+        let mut rng = SplitMix64(seed);
         Zobrist {
-            pawn_white: (0..63).map(|_| rng.gen()).collect(),
-            knight_white: (0..63).map(|_| rng.gen()).collect(),
-            bishop_white: (0..63).map(|_| rng.gen()).collect(),
-            rook_white: (0..63).map(|_| rng.gen()).collect(),
-            queen_white: (0..63).map(|_| rng.gen()).collect(),
-            king_white: (0..63).map(|_| rng.gen()).collect(),
-            pawn_black: (0..63).map(|_| rng.gen()).collect(),
-            knight_black: (0..63).map(|_| rng.gen()).collect(),
-            bishop_black: (0..63).map(|_| rng.gen()).collect(),
-            rook_black: (0..63).map(|_| rng.gen()).collect(),
-            queen_black: (0..63).map(|_| rng.gen()).collect(),
-            king_black: (0..63).map(|_| rng.gen()).collect(),
-            move_black: rng.gen(),
-            castling_king_black: rng.gen(),
-            castling_queen_black: rng.gen(),
-            castling_king_white: rng.gen(),
-            castling_queen_white: rng.gen(),
-            passant_a: rng.gen(),
-            passant_b: rng.gen(),
-            passant_c: rng.gen(),
-            passant_d: rng.gen(),
-            passant_e: rng.gen(),
-            passant_f: rng.gen(),
-            passant_g: rng.gen(),
-            passant_h: rng.gen(),
+            pawn_white: rng.table(),
+            knight_white: rng.table(),
+            bishop_white: rng.table(),
+            rook_white: rng.table(),
+            queen_white: rng.table(),
+            king_white: rng.table(),
+            pawn_black: rng.table(),
+            knight_black: rng.table(),
+            bishop_black: rng.table(),
+            rook_black: rng.table(),
+            queen_black: rng.table(),
+            king_black: rng.table(),
+            move_black: rng.next(),
+            castling_king_black: rng.next(),
+            castling_queen_black: rng.next(),
+            castling_king_white: rng.next(),
+            castling_queen_white: rng.next(),
+            passant_a: rng.next(),
+            passant_b: rng.next(),
+            passant_c: rng.next(),
+            passant_d: rng.next(),
+            passant_e: rng.next(),
+            passant_f: rng.next(),
+            passant_g: rng.next(),
+            passant_h: rng.next(),
+        }
+    }
+
+    /// Returns the key of a piece standing on a square
+    pub fn piece(&self, side: Side, piece: PieceType, square: Square) -> u64 {
+        let table = match (side, piece) {
+            (Side::White, PieceType::Pawn) => &self.pawn_white,
+            (Side::White, PieceType::Knight) => &self.knight_white,
+            (Side::White, PieceType::Bishop) => &self.bishop_white,
+            (Side::White, PieceType::Rook) => &self.rook_white,
+            (Side::White, PieceType::Queen) => &self.queen_white,
+            (Side::White, PieceType::King) => &self.king_white,
+            (Side::Black, PieceType::Pawn) => &self.pawn_black,
+            (Side::Black, PieceType::Knight) => &self.knight_black,
+            (Side::Black, PieceType::Bishop) => &self.bishop_black,
+            (Side::Black, PieceType::Rook) => &self.rook_black,
+            (Side::Black, PieceType::Queen) => &self.queen_black,
+            (Side::Black, PieceType::King) => &self.king_black,
+        };
+        table[square]
+    }
+
+    /// Returns the combined key of the given castling bits
+    pub fn castling(&self, rights: u8) -> u64 {
+        let mut key = 0;
+        for (right, right_key) in [
+            (Castling::WHITE_00, self.castling_king_white),
+            (Castling::WHITE_000, self.castling_queen_white),
+            (Castling::BLACK_00, self.castling_king_black),
+            (Castling::BLACK_000, self.castling_queen_black),
+        ] {
+            if rights & right != 0 {
+                key ^= right_key;
+            }
         }
+        key
+    }
+
+    /// Returns the key of the en passant file of `square`, or 0 without one
+    pub fn en_passant(&self, square: Option<Square>) -> u64 {
+        match square.map(|square| square % 8) {
+            Some(0) => self.passant_a,
+            Some(1) => self.passant_b,
+            Some(2) => self.passant_c,
+            Some(3) => self.passant_d,
+            Some(4) => self.passant_e,
+            Some(5) => self.passant_f,
+            Some(6) => self.passant_g,
+            Some(7) => self.passant_h,
+            _ => 0,
+        }
+    }
+
+    /// Returns the key xored in when black is to move
+    pub fn side(&self) -> u64 {
+        self.move_black
+    }
+
+    /// Computes the hash of a position from scratch
+    pub fn hash(&self, board: &Board) -> u64 {
+        let mut hash = 0;
+        for side in [Side::White, Side::Black] {
+            for piece in PieceType::ALL {
+                let mut set = board.pieces(side, piece);
+                while set != 0 {
+                    hash ^= self.piece(side, piece, set.trailing_zeros() as Square);
+                    set &= set - 1;
+                }
+            }
+        }
+
+        let state = board.state();
+        hash ^= self.castling(state.castling_rights().bits());
+        hash ^= self.en_passant(state.en_passant_square());
+        if state.stm() == Side::Black {
+            hash ^= self.side();
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keys() {
+        let keys = Zobrist::default();
+        // h8 has a key of its own
+        assert_ne!(keys.piece(Side::Black, PieceType::Rook, 63), 0);
+        assert_eq!(keys.castling(Castling::NO_CASTLING), 0);
+        assert_eq!(
+            keys.castling(Castling::ANY_CASTLING),
+            keys.castling(Castling::WHITE_CASTLING) ^ keys.castling(Castling::BLACK_CASTLING)
+        );
+        assert_eq!(keys.en_passant(None), 0);
+        assert_eq!(keys.en_passant(Some(20)), keys.en_passant(Some(44)));
+    }
+
+    #[test]
+    fn test_seeds() {
+        assert_eq!(Zobrist::new(42), Zobrist::new(42));
+        assert_ne!(Zobrist::new(42), Zobrist::new(43));
+
+        let board = Board::default();
+        assert_eq!(KEYS.hash(&board), board.hash());
+        assert_ne!(Zobrist::new(42).hash(&board), board.hash());
+        // Pinned so that a change to the key generation is noticed
+        assert_eq!(board.hash(), 0x8ff6_f282_e19d_060d);
     }
 }